@@ -0,0 +1,121 @@
+use super::model::{Model, Parameter, Settings, Value};
+use super::{classes, features_and_target, softmax_rows};
+use ndarray::prelude::*;
+use std::f64::consts::PI;
+
+// Blank or a comma separated list of positive priors.
+fn parse_priors(text: &str) -> Result<Vec<f64>, String> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    text.split(',')
+        .map(|prior| match prior.trim().parse::<f64>() {
+            Ok(prior) if prior > 0. && prior.is_finite() => Ok(prior),
+            _ => Err(format!("\"{}\" isn't a positive number", prior.trim())),
+        })
+        .collect()
+}
+
+// Models every feature as normally distributed within each class,
+// independently of the other features.
+pub struct GaussianNaiveBayes {
+    // comma separated prior of every class, blank (or a prior short for some
+    // class) takes the class frequencies of the training set
+    priors: String,
+    var_smoothing: f64,
+    means: Array2<f64>,
+    variances: Array2<f64>,
+    log_priors: Array1<f64>,
+    classes: Vec<f64>,
+}
+
+impl GaussianNaiveBayes {
+    pub fn new(settings: &Settings) -> Self {
+        GaussianNaiveBayes {
+            priors: settings.text("Priors", ""),
+            var_smoothing: settings.number("Var Smoothing", 1e-9),
+            means: Array2::zeros([0, 0]),
+            variances: Array2::zeros([0, 0]),
+            log_priors: Array1::zeros(0),
+            classes: Vec::new(),
+        }
+    }
+}
+
+impl Model for GaussianNaiveBayes {
+    fn fit(&mut self, train_set: &Array2<f64>, _validation_set: Option<&Array2<f64>>) {
+        let (features, targets) = features_and_target(train_set);
+
+        self.classes = classes(&targets);
+        let (n_classes, n_features) = (self.classes.len(), features.ncols());
+        self.means = Array2::zeros([n_classes, n_features]);
+        self.variances = Array2::zeros([n_classes, n_features]);
+
+        // a fraction of the largest feature variance is added to every
+        // variance so constant features don't divide by zero
+        let epsilon = self.var_smoothing
+            * features
+                .var_axis(Axis(0), 0.)
+                .fold(0., |acc: f64, &v| acc.max(v));
+
+        let mut frequencies = Array1::zeros(n_classes);
+        for (idx, class) in self.classes.iter().enumerate() {
+            let rows: Vec<usize> = (0..targets.len())
+                .filter(|&row| targets[row] == *class)
+                .collect();
+            let samples = features.select(Axis(0), &rows);
+
+            self.means
+                .row_mut(idx)
+                .assign(&samples.mean_axis(Axis(0)).unwrap());
+            self.variances
+                .row_mut(idx)
+                .assign(&samples.var_axis(Axis(0), 0.).mapv(|v| v + epsilon));
+            frequencies[idx] = rows.len() as f64 / targets.len() as f64;
+        }
+
+        // the form can't tell how many classes there are
+        let priors = match parse_priors(&self.priors) {
+            Ok(priors) if priors.len() == n_classes => {
+                let priors = Array1::from(priors);
+                &priors / priors.sum()
+            }
+            _ => frequencies,
+        };
+        self.log_priors = priors.mapv(f64::ln);
+    }
+
+    fn predict_proba(&self, features: &Array2<f64>) -> Option<Array2<f64>> {
+        let mut probabilities = Array2::zeros([features.nrows(), self.classes.len()]);
+
+        // log joint likelihoods first, they underflow easily outside log space
+        for (mut row, sample) in probabilities.outer_iter_mut().zip(features.outer_iter()) {
+            for (class, p) in row.iter_mut().enumerate() {
+                let (means, variances) = (self.means.row(class), self.variances.row(class));
+                let log_likelihood: f64 = sample
+                    .iter()
+                    .zip(means.iter().zip(variances.iter()))
+                    .map(|(x, (mean, variance))| {
+                        -0.5 * (2. * PI * variance).ln() - (x - mean).powi(2) / (2. * variance)
+                    })
+                    .sum();
+                *p = self.log_priors[class] + log_likelihood;
+            }
+        }
+
+        Some(softmax_rows(probabilities))
+    }
+
+    fn classes(&self) -> &[f64] {
+        &self.classes
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![
+            Parameter::new("Priors", Value::Text(self.priors.clone()))
+                .checked_with(|text| parse_priors(text).map(|_| ())),
+            Parameter::new("Var Smoothing", Value::Number(self.var_smoothing)),
+        ]
+    }
+}
@@ -0,0 +1,275 @@
+use super::model::{Model, Parameter, Settings, Value};
+use super::{
+    class_probabilities, classes, encode, features_and_target, sigmoid, softmax_rows, History,
+};
+use ndarray::prelude::*;
+
+// A regression tree fitted to the log-loss gradients, every leaf holds a
+// Newton step for the scores of the samples that end up in it.
+struct RegressionNode {
+    value: f64,
+    split: Option<(usize, f64, Box<RegressionNode>, Box<RegressionNode>)>,
+}
+
+impl RegressionNode {
+    fn predict(&self, sample: ArrayView1<f64>) -> f64 {
+        match &self.split {
+            Some((feature, threshold, left, _)) if sample[*feature] <= *threshold => {
+                left.predict(sample)
+            }
+            Some((_, _, _, right)) => right.predict(sample),
+            None => self.value,
+        }
+    }
+}
+
+// Residuals and second derivatives of the loss for every training row.
+struct Gradients<'a> {
+    residuals: ArrayView1<'a, f64>,
+    hessians: ArrayView1<'a, f64>,
+    // scales the leaf values, (K - 1) / K for K classes and 1 for two
+    scale: f64,
+}
+
+fn grow(
+    features: &Array2<f64>,
+    gradients: &Gradients,
+    rows: Vec<usize>,
+    depth: usize,
+    max_depth: usize,
+) -> RegressionNode {
+    let residual_sum: f64 = rows.iter().map(|&row| gradients.residuals[row]).sum();
+    let hessian_sum: f64 = rows.iter().map(|&row| gradients.hessians[row]).sum();
+    let value = gradients.scale * residual_sum / hessian_sum.max(1e-12);
+
+    if depth >= max_depth || rows.len() < 2 {
+        return RegressionNode { value, split: None };
+    }
+
+    // the split that reduces the squared error of the residuals the most
+    let mut best: Option<(f64, usize, f64)> = None;
+    for feature in 0..features.ncols() {
+        let mut sorted = rows.clone();
+        sorted.sort_by(|&a, &b| {
+            features[[a, feature]]
+                .partial_cmp(&features[[b, feature]])
+                .expect("Tried to compare a NaN")
+        });
+
+        let mut left_sum = 0.;
+        for idx in 0..sorted.len() - 1 {
+            left_sum += gradients.residuals[sorted[idx]];
+            let (value, next) = (
+                features[[sorted[idx], feature]],
+                features[[sorted[idx + 1], feature]],
+            );
+            if value == next {
+                continue;
+            }
+
+            let (left_size, right_size) = ((idx + 1) as f64, (sorted.len() - idx - 1) as f64);
+            let right_sum = residual_sum - left_sum;
+            let score = left_sum * left_sum / left_size + right_sum * right_sum / right_size;
+            let better = match best {
+                Some((highest, _, _)) => score > highest,
+                None => true,
+            };
+            if better {
+                best = Some((score, feature, (value + next) / 2.));
+            }
+        }
+    }
+
+    let split = best.map(|(_, feature, threshold)| {
+        let (left, right): (Vec<usize>, Vec<usize>) = rows
+            .iter()
+            .partition(|&&row| features[[row, feature]] <= threshold);
+        (
+            feature,
+            threshold,
+            Box::new(grow(features, gradients, left, depth + 1, max_depth)),
+            Box::new(grow(features, gradients, right, depth + 1, max_depth)),
+        )
+    });
+
+    RegressionNode { value, split }
+}
+
+// Scores have one column for binary targets and one per class otherwise.
+fn probabilities(scores: &Array2<f64>) -> Array2<f64> {
+    if scores.ncols() == 1 {
+        scores.mapv(sigmoid)
+    } else {
+        softmax_rows(scores.clone())
+    }
+}
+
+fn log_loss(probabilities: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    let clamped = probabilities.mapv(|p| p.clamp(1e-15, 1. - 1e-15));
+    let loss = if y.ncols() == 1 {
+        y * &clamped.mapv(f64::ln) + &(y.mapv(|z| 1. - z) * &clamped.mapv(|p| (1. - p).ln()))
+    } else {
+        y * &clamped.mapv(f64::ln)
+    };
+
+    -loss.sum() / y.nrows() as f64
+}
+
+// Every boosting round fits one tree per score column to the residuals of the
+// rounds before it, shrunk by the learning rate.
+pub struct GradientBoosting {
+    learning_rate: f64,
+    estimators: usize,
+    max_depth: usize,
+    initial: Array1<f64>,
+    rounds: Vec<Vec<RegressionNode>>,
+    classes: Vec<f64>,
+    history: History,
+}
+
+impl GradientBoosting {
+    pub fn new(settings: &Settings) -> Self {
+        GradientBoosting {
+            learning_rate: settings.number("Learning Rate", 0.1),
+            estimators: settings.integer("Estimators", 100),
+            max_depth: settings.integer("Max Depth", 3),
+            initial: Array1::zeros(0),
+            rounds: Vec::new(),
+            classes: Vec::new(),
+            history: History::default(),
+        }
+    }
+
+    fn initial_scores(&self, samples: usize) -> Array2<f64> {
+        let mut scores = Array2::zeros([samples, self.initial.len()]);
+        scores += &self.initial;
+        scores
+    }
+
+    fn add_round(
+        &self,
+        scores: &mut Array2<f64>,
+        features: &Array2<f64>,
+        round: &[RegressionNode],
+    ) {
+        for (column, tree) in round.iter().enumerate() {
+            for (row, sample) in features.outer_iter().enumerate() {
+                scores[[row, column]] += self.learning_rate * tree.predict(sample);
+            }
+        }
+    }
+
+    fn scores(&self, features: &Array2<f64>) -> Array2<f64> {
+        let mut scores = self.initial_scores(features.nrows());
+        for round in self.rounds.iter() {
+            self.add_round(&mut scores, features, round);
+        }
+        scores
+    }
+
+    // The log-loss on `set` after every boosting round.
+    fn staged_losses(&self, set: &Array2<f64>) -> Vec<f64> {
+        let (features, targets) = features_and_target(set);
+        let y = encode(&targets, &self.classes).t().to_owned();
+
+        let mut scores = self.initial_scores(features.nrows());
+        self.rounds
+            .iter()
+            .map(|round| {
+                self.add_round(&mut scores, &features, round);
+                log_loss(&probabilities(&scores), &y)
+            })
+            .collect()
+    }
+}
+
+impl Model for GradientBoosting {
+    fn fit(&mut self, train_set: &Array2<f64>, validation_set: Option<&Array2<f64>>) {
+        let (features, targets) = features_and_target(train_set);
+
+        self.classes = classes(&targets);
+        let y = encode(&targets, &self.classes).t().to_owned();
+        let outputs = y.ncols();
+
+        // start from the log odds of the class priors
+        let priors = y.mean_axis(Axis(0)).unwrap();
+        self.initial = if outputs == 1 {
+            priors.mapv(|p| (p.max(1e-15) / (1. - p).max(1e-15)).ln())
+        } else {
+            priors.mapv(|p| p.max(1e-15).ln())
+        };
+        let scale = if outputs == 1 {
+            1.
+        } else {
+            (outputs - 1) as f64 / outputs as f64
+        };
+
+        self.rounds = Vec::new();
+        self.history = History::default();
+        let mut scores = self.initial_scores(features.nrows());
+        for _ in 0..self.estimators {
+            let p = probabilities(&scores);
+            let residuals = &y - &p;
+            let hessians = p.mapv(|p| p * (1. - p));
+
+            let round: Vec<RegressionNode> = (0..outputs)
+                .map(|column| {
+                    let gradients = Gradients {
+                        residuals: residuals.column(column),
+                        hessians: hessians.column(column),
+                        scale,
+                    };
+                    grow(
+                        &features,
+                        &gradients,
+                        (0..features.nrows()).collect(),
+                        0,
+                        self.max_depth,
+                    )
+                })
+                .collect();
+
+            self.add_round(&mut scores, &features, &round);
+            self.rounds.push(round);
+            self.history
+                .costs
+                .push(log_loss(&probabilities(&scores), &y));
+        }
+
+        if let Some(validation_set) = validation_set {
+            self.history.validation_costs = self
+                .staged_losses(validation_set)
+                .into_iter()
+                .enumerate()
+                .map(|(round, loss)| (round + 1, loss))
+                .collect();
+        }
+    }
+
+    fn predict_proba(&self, features: &Array2<f64>) -> Option<Array2<f64>> {
+        Some(class_probabilities(
+            self.classes.len(),
+            probabilities(&self.scores(features)),
+        ))
+    }
+
+    fn classes(&self) -> &[f64] {
+        &self.classes
+    }
+
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![
+            Parameter::new("Learning Rate", Value::Number(self.learning_rate)),
+            Parameter::new("Estimators", Value::Integer(self.estimators)),
+            Parameter::new("Max Depth", Value::Integer(self.max_depth)),
+        ]
+    }
+
+    fn staged_costs(&self, set: &Array2<f64>) -> Option<Vec<f64>> {
+        Some(self.staged_losses(set))
+    }
+}
@@ -0,0 +1,271 @@
+use ndarray::prelude::*;
+
+// How the one-vs-rest metrics of every class are combined for targets with
+// more than two classes.
+pub const AVERAGES: [&str; 3] = ["Macro", "Micro", "Weighted"];
+
+// 0 instead of NaN when nothing was counted, like a class never predicted.
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0. {
+        numerator / denominator
+    } else {
+        0.
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfusionMatrix {
+    pub classes: Vec<f64>,
+    // rows are the actual classes and columns the predicted ones
+    pub counts: Array2<usize>,
+}
+
+impl ConfusionMatrix {
+    pub fn new(actual: &Array1<f64>, predicted: &Array1<f64>, classes: &[f64]) -> Self {
+        let mut counts = Array2::zeros([classes.len(), classes.len()]);
+        for (actual, predicted) in actual.iter().zip(predicted.iter()) {
+            let row = classes.iter().position(|class| class == actual);
+            let col = classes.iter().position(|class| class == predicted);
+            if let (Some(row), Some(col)) = (row, col) {
+                counts[[row, col]] += 1;
+            }
+        }
+
+        ConfusionMatrix {
+            classes: classes.to_vec(),
+            counts,
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.counts.sum() as f64
+    }
+
+    pub fn correct(&self) -> f64 {
+        self.counts.diag().sum() as f64
+    }
+
+    // Samples of `class` that were predicted as it.
+    pub fn true_positives(&self, class: usize) -> f64 {
+        self.counts[[class, class]] as f64
+    }
+
+    pub fn false_positives(&self, class: usize) -> f64 {
+        self.counts.column(class).sum() as f64 - self.true_positives(class)
+    }
+
+    pub fn false_negatives(&self, class: usize) -> f64 {
+        self.counts.row(class).sum() as f64 - self.true_positives(class)
+    }
+
+    pub fn true_negatives(&self, class: usize) -> f64 {
+        self.total()
+            - self.true_positives(class)
+            - self.false_positives(class)
+            - self.false_negatives(class)
+    }
+
+    // Number of samples actually in `class`.
+    pub fn support(&self, class: usize) -> f64 {
+        self.counts.row(class).sum() as f64
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.correct(), self.total())
+    }
+
+    pub fn precision(&self, class: usize) -> f64 {
+        let true_positives = self.true_positives(class);
+        ratio(true_positives, true_positives + self.false_positives(class))
+    }
+
+    pub fn recall(&self, class: usize) -> f64 {
+        let true_positives = self.true_positives(class);
+        ratio(true_positives, true_positives + self.false_negatives(class))
+    }
+
+    pub fn specificity(&self, class: usize) -> f64 {
+        let true_negatives = self.true_negatives(class);
+        ratio(true_negatives, true_negatives + self.false_positives(class))
+    }
+
+    pub fn f1_score(&self, class: usize) -> f64 {
+        let (precision, recall) = (self.precision(class), self.recall(class));
+        ratio(2. * precision * recall, precision + recall)
+    }
+
+    // Mean recall over the classes.
+    pub fn balanced_accuracy(&self) -> f64 {
+        let n = self.classes.len();
+        (0..n).map(|class| self.recall(class)).sum::<f64>() / n.max(1) as f64
+    }
+
+    // Matthews correlation coefficient, the multi-class form reduces to the
+    // usual binary one.
+    pub fn matthews_correlation(&self) -> f64 {
+        let total = self.total();
+        let actual = self.counts.sum_axis(Axis(1)).mapv(|count| count as f64);
+        let predicted = self.counts.sum_axis(Axis(0)).mapv(|count| count as f64);
+
+        let covariance = self.correct() * total - actual.dot(&predicted);
+        let spread = ((total * total - predicted.dot(&predicted))
+            * (total * total - actual.dot(&actual)))
+        .sqrt();
+        ratio(covariance, spread)
+    }
+
+    // Agreement beyond what matching the class frequencies would give by
+    // chance.
+    pub fn cohen_kappa(&self) -> f64 {
+        let total = self.total();
+        let actual = self.counts.sum_axis(Axis(1)).mapv(|count| count as f64);
+        let predicted = self.counts.sum_axis(Axis(0)).mapv(|count| count as f64);
+
+        let observed = self.accuracy();
+        let expected = ratio(actual.dot(&predicted), total * total);
+        ratio(observed - expected, 1. - expected)
+    }
+
+    fn average(&self, average: &str, per_class: &dyn Fn(usize) -> f64) -> f64 {
+        let n = self.classes.len();
+        match average {
+            "Macro" => (0..n).map(per_class).sum::<f64>() / n.max(1) as f64,
+            "Weighted" => ratio(
+                (0..n)
+                    .map(|class| per_class(class) * self.support(class))
+                    .sum(),
+                self.total(),
+            ),
+            _ => panic!("unknown average {}", average),
+        }
+    }
+
+    // Precision, recall, specificity and F1 of every class combined, micro
+    // averaging sums the counts of every class first.
+    fn averaged(&self, average: &str) -> (f64, f64, f64, f64) {
+        if average == "Micro" {
+            let n = self.classes.len();
+            let sum = |count: &dyn Fn(usize) -> f64| (0..n).map(count).sum::<f64>();
+            let true_positives = sum(&|class| self.true_positives(class));
+            let false_positives = sum(&|class| self.false_positives(class));
+            let false_negatives = sum(&|class| self.false_negatives(class));
+            let true_negatives = sum(&|class| self.true_negatives(class));
+
+            let precision = ratio(true_positives, true_positives + false_positives);
+            let recall = ratio(true_positives, true_positives + false_negatives);
+            return (
+                precision,
+                recall,
+                ratio(true_negatives, true_negatives + false_positives),
+                ratio(2. * precision * recall, precision + recall),
+            );
+        }
+
+        (
+            self.average(average, &|class| self.precision(class)),
+            self.average(average, &|class| self.recall(class)),
+            self.average(average, &|class| self.specificity(class)),
+            self.average(average, &|class| self.f1_score(class)),
+        )
+    }
+
+    // Binary targets are scored on the positive (last) class, `average` is
+    // only used with more classes.
+    pub fn metrics(&self, average: &str) -> Vec<(&'static str, f64)> {
+        let (precision, recall, specificity, f1_score) = if self.classes.len() == 2 {
+            (
+                self.precision(1),
+                self.recall(1),
+                self.specificity(1),
+                self.f1_score(1),
+            )
+        } else {
+            self.averaged(average)
+        };
+
+        vec![
+            ("Accuracy", self.accuracy()),
+            ("Precision", precision),
+            ("Recall", recall),
+            ("Specificity", specificity),
+            ("F1 Score", f1_score),
+            ("Balanced Accuracy", self.balanced_accuracy()),
+            ("MCC", self.matthews_correlation()),
+            ("Cohen's Kappa", self.cohen_kappa()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 4 actual positives and 6 negatives: TP 3, FN 1, FP 2, TN 4
+    fn binary() -> ConfusionMatrix {
+        let actual = array![1., 1., 1., 1., 0., 0., 0., 0., 0., 0.];
+        let predicted = array![1., 1., 1., 0., 1., 1., 0., 0., 0., 0.];
+        ConfusionMatrix::new(&actual, &predicted, &[0., 1.])
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn binary_counts_are_oriented_by_actual_rows() {
+        let matrix = binary();
+        assert_eq!(matrix.counts, array![[4, 2], [1, 3]]);
+        assert_close(matrix.true_positives(1), 3.);
+        assert_close(matrix.false_positives(1), 2.);
+        assert_close(matrix.false_negatives(1), 1.);
+        assert_close(matrix.true_negatives(1), 4.);
+    }
+
+    #[test]
+    fn binary_metrics() {
+        let metrics = binary().metrics("Macro");
+        let metric = |name| metrics.iter().find(|(other, _)| *other == name).unwrap().1;
+
+        assert_close(metric("Accuracy"), 0.7);
+        assert_close(metric("Precision"), 0.6);
+        assert_close(metric("Recall"), 0.75);
+        assert_close(metric("Specificity"), 4. / 6.);
+        assert_close(metric("F1 Score"), 2. / 3.);
+        assert_close(metric("Balanced Accuracy"), (0.75 + 4. / 6.) / 2.);
+    }
+
+    #[test]
+    fn matthews_correlation() {
+        // (3 * 4 - 2 * 1) / sqrt(5 * 5 * 4 * 6)
+        assert_close(binary().matthews_correlation(), 10. / 600f64.sqrt());
+
+        let actual = array![0., 1., 0., 1.];
+        let perfect = ConfusionMatrix::new(&actual, &actual, &[0., 1.]);
+        assert_close(perfect.matthews_correlation(), 1.);
+        let inverted = ConfusionMatrix::new(&actual, &array![1., 0., 1., 0.], &[0., 1.]);
+        assert_close(inverted.matthews_correlation(), -1.);
+    }
+
+    #[test]
+    fn cohen_kappa() {
+        // observed 0.7, expected (6 * 5 + 4 * 5) / 100
+        assert_close(binary().cohen_kappa(), 0.4);
+    }
+
+    #[test]
+    fn multiclass_averages() {
+        let actual = array![0., 0., 1., 1., 2., 2.];
+        let predicted = array![0., 1., 1., 1., 2., 0.];
+        let matrix = ConfusionMatrix::new(&actual, &predicted, &[0., 1., 2.]);
+
+        // micro averaged precision and recall both come down to the accuracy
+        let (precision, recall, _, f1_score) = matrix.averaged("Micro");
+        assert_close(precision, 4. / 6.);
+        assert_close(recall, 4. / 6.);
+        assert_close(f1_score, 4. / 6.);
+
+        let (precision, recall, _, _) = matrix.averaged("Macro");
+        assert_close(precision, (0.5 + 2. / 3. + 1.) / 3.);
+        assert_close(recall, (0.5 + 1. + 0.5) / 3.);
+    }
+}
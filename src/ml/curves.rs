@@ -0,0 +1,248 @@
+use ndarray::prelude::*;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RocPoint {
+    // samples scoring at least this much are predicted positive
+    pub threshold: f64,
+    pub false_positive_rate: f64,
+    pub true_positive_rate: f64,
+}
+
+// Indices of the samples by decreasing score.
+fn by_score(scores: ArrayView1<f64>) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| {
+        scores[b]
+            .partial_cmp(&scores[a])
+            .expect("Tried to compare a NaN")
+    });
+    order
+}
+
+// (threshold, true positives, false positives) for every distinct score, by
+// decreasing threshold.
+fn counts_by_threshold(
+    actual: ArrayView1<f64>,
+    scores: ArrayView1<f64>,
+    positive: f64,
+) -> Vec<(f64, f64, f64)> {
+    let mut counts = Vec::new();
+    let (mut true_positives, mut false_positives) = (0., 0.);
+
+    let order = by_score(scores);
+    for (idx, &sample) in order.iter().enumerate() {
+        if actual[sample] == positive {
+            true_positives += 1.;
+        } else {
+            false_positives += 1.;
+        }
+
+        // ties are only split once every sample with the same score is in
+        if idx + 1 == order.len() || scores[order[idx + 1]] != scores[sample] {
+            counts.push((scores[sample], true_positives, false_positives));
+        }
+    }
+
+    counts
+}
+
+// One point for every distinct score, from (0, 0) with nothing predicted
+// positive to (1, 1) with everything predicted positive.
+pub fn roc_curve(actual: ArrayView1<f64>, scores: ArrayView1<f64>, positive: f64) -> Vec<RocPoint> {
+    let positives = actual.iter().filter(|&&y| y == positive).count() as f64;
+    let negatives = actual.len() as f64 - positives;
+    // a rate stays at 0 instead of NaN when its class is missing
+    let (positives, negatives) = (positives.max(1.), negatives.max(1.));
+
+    let mut points = vec![RocPoint {
+        threshold: f64::INFINITY,
+        false_positive_rate: 0.,
+        true_positive_rate: 0.,
+    }];
+    points.extend(
+        counts_by_threshold(actual, scores, positive)
+            .into_iter()
+            .map(|(threshold, true_positives, false_positives)| RocPoint {
+                threshold,
+                false_positive_rate: false_positives / negatives,
+                true_positive_rate: true_positives / positives,
+            }),
+    );
+
+    points
+}
+
+// Trapezoidal area under the ROC curve.
+pub fn roc_auc(points: &[RocPoint]) -> f64 {
+    points
+        .windows(2)
+        .map(|pair| {
+            (pair[1].false_positive_rate - pair[0].false_positive_rate)
+                * (pair[0].true_positive_rate + pair[1].true_positive_rate)
+                / 2.
+        })
+        .sum()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PrPoint {
+    pub threshold: f64,
+    pub precision: f64,
+    pub recall: f64,
+}
+
+impl PrPoint {
+    pub fn f1_score(&self) -> f64 {
+        if self.precision + self.recall > 0. {
+            2. * self.precision * self.recall / (self.precision + self.recall)
+        } else {
+            0.
+        }
+    }
+}
+
+// One point for every distinct score, by increasing recall.
+pub fn precision_recall_curve(
+    actual: ArrayView1<f64>,
+    scores: ArrayView1<f64>,
+    positive: f64,
+) -> Vec<PrPoint> {
+    let positives = actual.iter().filter(|&&y| y == positive).count().max(1) as f64;
+
+    counts_by_threshold(actual, scores, positive)
+        .into_iter()
+        .map(|(threshold, true_positives, false_positives)| PrPoint {
+            threshold,
+            precision: true_positives / (true_positives + false_positives),
+            recall: true_positives / positives,
+        })
+        .collect()
+}
+
+// Precision at every threshold weighted by the recall it adds, the area under
+// the step version of the curve.
+pub fn average_precision(points: &[PrPoint]) -> f64 {
+    let mut recall = 0.;
+    points
+        .iter()
+        .map(|point| {
+            let area = (point.recall - recall) * point.precision;
+            recall = point.recall;
+            area
+        })
+        .sum()
+}
+
+pub fn best_f1_threshold(points: &[PrPoint]) -> PrPoint {
+    *points
+        .iter()
+        .max_by(|a, b| {
+            a.f1_score()
+                .partial_cmp(&b.f1_score())
+                .expect("Tried to compare a NaN")
+        })
+        .unwrap()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CalibrationBin {
+    pub mean_probability: f64,
+    // share of the samples in the bin that are actually positive
+    pub observed_frequency: f64,
+    pub count: usize,
+}
+
+// Splits [0, 1] into `bins` equal bins of predicted positive probability,
+// empty bins are left out.
+pub fn calibration_curve(
+    actual: ArrayView1<f64>,
+    scores: ArrayView1<f64>,
+    positive: f64,
+    bins: usize,
+) -> Vec<CalibrationBin> {
+    let mut sums = vec![(0., 0., 0); bins];
+    for (&y, &score) in actual.iter().zip(scores.iter()) {
+        let bin = ((score * bins as f64) as usize).min(bins - 1);
+        sums[bin].0 += score;
+        sums[bin].1 += if y == positive { 1. } else { 0. };
+        sums[bin].2 += 1;
+    }
+
+    sums.into_iter()
+        .filter(|(_, _, count)| *count > 0)
+        .map(|(probability, positives, count)| CalibrationBin {
+            mean_probability: probability / count as f64,
+            observed_frequency: positives / count as f64,
+            count,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn roc_auc_ranks_positives_above_negatives() {
+        let actual = array![0., 0., 1., 1.];
+        let scores = array![0.1, 0.4, 0.35, 0.8];
+        let roc = roc_curve(actual.view(), scores.view(), 1.);
+
+        assert_close(roc_auc(&roc), 0.75);
+        let last = roc.last().unwrap();
+        assert_close(last.false_positive_rate, 1.);
+        assert_close(last.true_positive_rate, 1.);
+    }
+
+    #[test]
+    fn tied_scores_give_a_diagonal() {
+        let actual = array![0., 1., 0., 1.];
+        let scores = array![0.5, 0.5, 0.5, 0.5];
+        let roc = roc_curve(actual.view(), scores.view(), 1.);
+
+        assert_eq!(roc.len(), 2);
+        assert_close(roc_auc(&roc), 0.5);
+    }
+
+    #[test]
+    fn rates_stay_within_one_class() {
+        let scores = array![0.2, 0.6, 0.9];
+        for &positive in [0., 1.].iter() {
+            let roc = roc_curve(array![0., 0., 0.].view(), scores.view(), positive);
+            for point in roc.iter() {
+                assert!(point.false_positive_rate <= 1. && point.true_positive_rate <= 1.);
+            }
+        }
+    }
+
+    #[test]
+    fn average_precision_and_best_f1() {
+        let actual = array![0., 0., 1., 1.];
+        let scores = array![0.1, 0.4, 0.35, 0.8];
+        let pr = precision_recall_curve(actual.view(), scores.view(), 1.);
+
+        assert_close(average_precision(&pr), 5. / 6.);
+
+        // everything above 0.35 is predicted positive: precision 2/3, recall 1
+        let best = best_f1_threshold(&pr);
+        assert_close(best.threshold, 0.35);
+        assert_close(best.f1_score(), 0.8);
+    }
+
+    #[test]
+    fn calibration_bins() {
+        let actual = array![0., 1., 1., 1.];
+        let scores = array![0.05, 0.15, 0.95, 0.85];
+        let bins = calibration_curve(actual.view(), scores.view(), 1., 2);
+
+        assert_eq!(bins.len(), 2);
+        assert_close(bins[0].mean_probability, 0.1);
+        assert_close(bins[0].observed_frequency, 0.5);
+        assert_close(bins[1].mean_probability, 0.9);
+        assert_close(bins[1].observed_frequency, 1.);
+    }
+}
@@ -0,0 +1,237 @@
+use super::model::{Parameter, Settings, Value};
+use super::optimizer::{self, Hyperparameters};
+use super::regularization::{self, Regularization};
+use super::{random_shuffle, seeded_rng};
+use super::{EarlyStopping, History, TrainOptions};
+use ndarray::prelude::*;
+
+// Features with one sample per column and targets with one row per output.
+pub type Samples = (Array2<f64>, Array2<f64>);
+
+// Returns the cost of a batch and the gradient of every parameter, in the
+// order the parameters are passed in.
+pub type ForwardBackward<'a> =
+    &'a dyn Fn(&[Array2<f64>], &Array2<f64>, &Array2<f64>) -> (f64, Vec<Array2<f64>>);
+
+// Returns the unregularized cost, used to score the validation set.
+pub type Cost<'a> = &'a dyn Fn(&[Array2<f64>], &Array2<f64>, &Array2<f64>) -> f64;
+
+// "all" or blank trains on every sample at once, otherwise it's the number
+// of samples in a batch.
+fn parse_batch_size(text: &str) -> Result<Option<usize>, String> {
+    match text.trim() {
+        "all" | "" => Ok(None),
+        size => size
+            .parse::<usize>()
+            .map(Some)
+            .map_err(|_| format!("\"{}\" isn't a number of samples or \"all\"", size)),
+    }
+}
+
+// The training settings shared by every model fitted with gradient descent.
+pub struct GradientDescent {
+    optimizer: String,
+    hyperparameters: Hyperparameters,
+    // kept as typed so the strength survives picking "None" for a while
+    regularization: (String, f64, f64),
+    pub options: TrainOptions,
+}
+
+impl GradientDescent {
+    pub fn new(settings: &Settings, learning_rate: f64, iterations: usize) -> Self {
+        let batch_size = parse_batch_size(&settings.text("Batch Size", "all")).unwrap();
+        let early_stopping = if settings.flag("Early Stopping", false) {
+            Some(EarlyStopping {
+                validation_ratio: settings.number("Holdout Ratio", 0.2),
+                patience: settings.integer("Patience", 10),
+            })
+        } else {
+            None
+        };
+
+        let regularization = (
+            settings.choice("Regularization", "None"),
+            settings.number("Strength", 0.01),
+            settings.number("L1 Ratio", 0.5),
+        );
+
+        GradientDescent {
+            optimizer: settings.choice("Optimizer", "SGD"),
+            hyperparameters: Hyperparameters {
+                learning_rate: settings.number("Learning Rate", learning_rate),
+                beta1: settings.number("Beta1", 0.9),
+                beta2: settings.number("Beta2", 0.999),
+                epsilon: settings.number("Epsilon", 1e-8),
+            },
+            options: TrainOptions {
+                iterations: settings.integer("Iterations", iterations),
+                batch_size,
+                regularization: Regularization::new(
+                    &regularization.0,
+                    regularization.1,
+                    regularization.2,
+                ),
+                early_stopping,
+                seed: settings.seed,
+            },
+            regularization,
+        }
+    }
+
+    pub fn parameters(&self) -> Vec<Parameter> {
+        let batch_size = match self.options.batch_size {
+            Some(size) => size.to_string(),
+            None => "all".to_string(),
+        };
+        let early_stopping = self.options.early_stopping.unwrap_or(EarlyStopping {
+            validation_ratio: 0.2,
+            patience: 10,
+        });
+
+        vec![
+            Parameter::new(
+                "Learning Rate",
+                Value::Number(self.hyperparameters.learning_rate),
+            ),
+            Parameter::new("Iterations", Value::Integer(self.options.iterations)),
+            Parameter::new("Batch Size", Value::Text(batch_size))
+                .checked_with(|text| parse_batch_size(text).map(|_| ())),
+            Parameter::new(
+                "Optimizer",
+                Value::Choice(&optimizer::OPTIMIZERS, self.optimizer.clone()),
+            ),
+            Parameter::new("Beta1", Value::Number(self.hyperparameters.beta1))
+                .enabled_when("Optimizer", &["Momentum", "Nesterov", "Adam"]),
+            Parameter::new("Beta2", Value::Number(self.hyperparameters.beta2))
+                .enabled_when("Optimizer", &["RMSProp", "Adam"]),
+            Parameter::new("Epsilon", Value::Number(self.hyperparameters.epsilon))
+                .enabled_when("Optimizer", &["RMSProp", "Adam"]),
+            Parameter::new(
+                "Regularization",
+                Value::Choice(
+                    &regularization::REGULARIZATIONS,
+                    self.regularization.0.clone(),
+                ),
+            ),
+            Parameter::new("Strength", Value::Number(self.regularization.1))
+                .enabled_when("Regularization", &["L1", "L2", "Elastic-Net"]),
+            Parameter::new("L1 Ratio", Value::Number(self.regularization.2))
+                .enabled_when("Regularization", &["Elastic-Net"]),
+            Parameter::new(
+                "Early Stopping",
+                Value::Flag(self.options.early_stopping.is_some()),
+            ),
+            Parameter::new(
+                "Holdout Ratio",
+                Value::Number(early_stopping.validation_ratio),
+            )
+            .enabled_when("Early Stopping", &["true"]),
+            Parameter::new("Patience", Value::Integer(early_stopping.patience))
+                .enabled_when("Early Stopping", &["true"]),
+        ]
+    }
+
+    // Without a validation set, early stopping holds back the last columns of
+    // the training samples instead.
+    pub fn holdout(
+        &self,
+        train: Samples,
+        validation: Option<Samples>,
+    ) -> (Samples, Option<Samples>) {
+        match (validation, self.options.early_stopping) {
+            (Some(validation), _) => (train, Some(validation)),
+            (None, Some(early_stopping)) => {
+                let (x_train, y_train) = train;
+                // at least one sample is always left to train on
+                let split_point =
+                    (((1. - early_stopping.validation_ratio) * x_train.ncols() as f64) as usize)
+                        .max(1)
+                        .min(x_train.ncols());
+
+                (
+                    (
+                        x_train.slice(s![.., ..split_point]).to_owned(),
+                        y_train.slice(s![.., ..split_point]).to_owned(),
+                    ),
+                    Some((
+                        x_train.slice(s![.., split_point..]).to_owned(),
+                        y_train.slice(s![.., split_point..]).to_owned(),
+                    )),
+                )
+            }
+            (None, None) => (train, None),
+        }
+    }
+
+    // The validation cost is tracked whenever a validation set is available,
+    // with early stopping the best parameters seen are the ones returned.
+    pub fn train(
+        &self,
+        parameters: Vec<Array2<f64>>,
+        (x_train, y_train): Samples,
+        validation: Option<Samples>,
+        forward_backward: ForwardBackward,
+        validation_cost: Cost,
+    ) -> (History, Vec<Array2<f64>>) {
+        let options = &self.options;
+        let mut optimizer = optimizer::create(&self.optimizer, self.hyperparameters);
+        let mut history = History::default();
+        let mut parameters = parameters;
+
+        let mut rng = seeded_rng(options.seed);
+        let samples = x_train.ncols();
+        let batch_size = options.batch_size.unwrap_or(samples).min(samples).max(1);
+        let mut order: Vec<usize> = (0..samples).collect();
+
+        let mut best: Option<(f64, Vec<Array2<f64>>)> = None;
+        let mut epochs_without_improvement = 0;
+
+        for _ in 0..options.iterations {
+            if batch_size < samples {
+                random_shuffle(&mut order, &mut rng);
+            }
+
+            for batch in order.chunks(batch_size) {
+                let x_batch = x_train.select(Axis(1), batch);
+                let y_batch = y_train.select(Axis(1), batch);
+
+                let (cost, gradients) = forward_backward(&parameters, &x_batch, &y_batch);
+                let mut parameter_refs: Vec<&mut Array2<f64>> = parameters.iter_mut().collect();
+                optimizer.step(&mut parameter_refs, &gradients);
+
+                history.costs.push(cost);
+            }
+
+            if let Some((x_validation, y_validation)) = &validation {
+                let cost = validation_cost(&parameters, x_validation, y_validation);
+                history.validation_costs.push((history.costs.len(), cost));
+
+                let early_stopping = match options.early_stopping {
+                    Some(early_stopping) => early_stopping,
+                    None => continue,
+                };
+                let improved = match &best {
+                    Some((lowest, _)) => cost < *lowest,
+                    None => true,
+                };
+                if improved {
+                    best = Some((cost, parameters.clone()));
+                    history.best = Some(history.costs.len());
+                    epochs_without_improvement = 0;
+                } else {
+                    epochs_without_improvement += 1;
+                    if epochs_without_improvement >= early_stopping.patience {
+                        history.stopped = Some(history.costs.len());
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Some((_, best_parameters)) = best {
+            parameters = best_parameters;
+        }
+
+        (history, parameters)
+    }
+}
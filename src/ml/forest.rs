@@ -0,0 +1,194 @@
+use super::model::{Model, Parameter, Settings, Value};
+use super::tree::{self, class_indices, Node, TreeOptions};
+use super::{classes, features_and_target, seeded_rng};
+use ndarray::prelude::*;
+use rand::prelude::*;
+use std::sync::Arc;
+use std::thread;
+
+const MAX_FEATURES: [&str; 3] = ["Sqrt", "Log2", "All"];
+
+// Grows a tree on a bootstrap sample of the rows, returns it together with
+// which rows were left out of the sample.
+fn grow_bagged(
+    features: &Array2<f64>,
+    targets: &[usize],
+    n_classes: usize,
+    options: &TreeOptions,
+    seed: u64,
+) -> (Node, Vec<bool>) {
+    let mut rng = seeded_rng(Some(seed));
+    let samples = features.nrows();
+
+    let mut out_of_bag = vec![true; samples];
+    let rows: Vec<usize> = (0..samples)
+        .map(|_| {
+            let row = rng.gen_range(0..samples);
+            out_of_bag[row] = false;
+            row
+        })
+        .collect();
+
+    let root = tree::grow(features, targets, rows, n_classes, options, &mut rng);
+    (root, out_of_bag)
+}
+
+// Averages the class probabilities of trees grown on bootstrap samples, with
+// only a random subset of the features tried at every split.
+pub struct RandomForest {
+    trees: usize,
+    threads: usize,
+    max_features: String,
+    options: TreeOptions,
+    seed: Option<u64>,
+    forest: Vec<Node>,
+    classes: Vec<f64>,
+    out_of_bag_accuracy: Option<f64>,
+}
+
+impl RandomForest {
+    pub fn new(settings: &Settings) -> Self {
+        RandomForest {
+            // an empty forest has no votes to average
+            trees: settings.integer("Trees", 100).max(1),
+            threads: settings.integer("Threads", 4),
+            max_features: settings.choice("Max Features", "Sqrt"),
+            options: TreeOptions {
+                max_depth: settings.integer("Max Depth", 10),
+                min_samples_leaf: settings.integer("Min Samples Leaf", 1),
+                criterion: settings.choice("Criterion", "Gini"),
+                max_features: None,
+            },
+            seed: settings.seed,
+            forest: Vec::new(),
+            classes: Vec::new(),
+            out_of_bag_accuracy: None,
+        }
+    }
+}
+
+impl Model for RandomForest {
+    fn fit(&mut self, train_set: &Array2<f64>, _validation_set: Option<&Array2<f64>>) {
+        let (features, targets) = features_and_target(train_set);
+
+        self.classes = classes(&targets);
+        let n_classes = self.classes.len();
+        let n_features = features.ncols() as f64;
+        let mut options = self.options.clone();
+        options.max_features = match self.max_features.as_str() {
+            "Sqrt" => Some(n_features.sqrt().round() as usize),
+            "Log2" => Some(n_features.log2().round() as usize),
+            _ => None,
+        };
+
+        // every tree gets its own seed up front so the forest doesn't depend
+        // on how the trees are spread over the threads
+        let mut rng = seeded_rng(self.seed);
+        let seeds: Vec<u64> = (0..self.trees).map(|_| rng.gen()).collect();
+
+        let features = Arc::new(features);
+        let class_indices = Arc::new(class_indices(&targets, &self.classes));
+        let chunk_size = seeds.len().div_ceil(self.threads.max(1));
+        let handles: Vec<_> = seeds
+            .chunks(chunk_size.max(1))
+            .map(|chunk| {
+                let chunk = chunk.to_vec();
+                let features = Arc::clone(&features);
+                let class_indices = Arc::clone(&class_indices);
+                let options = options.clone();
+
+                thread::spawn(move || {
+                    chunk
+                        .into_iter()
+                        .map(|seed| {
+                            grow_bagged(&features, &class_indices, n_classes, &options, seed)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let grown: Vec<(Node, Vec<bool>)> = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect();
+
+        // every row is scored only by the trees that never saw it
+        let mut votes = Array2::<f64>::zeros([features.nrows(), n_classes]);
+        for (root, out_of_bag) in grown.iter() {
+            for (row, _) in out_of_bag.iter().enumerate().filter(|(_, &oob)| oob) {
+                let mut row_votes = votes.row_mut(row);
+                row_votes += &root.leaf(features.row(row)).probabilities();
+            }
+        }
+        let (mut scored, mut correct) = (0, 0);
+        for (row, row_votes) in votes.outer_iter().enumerate() {
+            if row_votes.sum() == 0. {
+                continue;
+            }
+            let (class, _) =
+                row_votes
+                    .iter()
+                    .enumerate()
+                    .fold((0, f64::NEG_INFINITY), |acc, (idx, &v)| {
+                        if v > acc.1 {
+                            (idx, v)
+                        } else {
+                            acc
+                        }
+                    });
+            scored += 1;
+            if class == class_indices[row] {
+                correct += 1;
+            }
+        }
+        self.out_of_bag_accuracy = if scored > 0 {
+            Some(correct as f64 / scored as f64)
+        } else {
+            None
+        };
+
+        self.forest = grown.into_iter().map(|(root, _)| root).collect();
+    }
+
+    fn predict_proba(&self, features: &Array2<f64>) -> Option<Array2<f64>> {
+        let mut probabilities = Array2::zeros([features.nrows(), self.classes.len()]);
+        for (mut row, sample) in probabilities.outer_iter_mut().zip(features.outer_iter()) {
+            for root in self.forest.iter() {
+                row += &root.leaf(sample).probabilities();
+            }
+        }
+        Some(probabilities / self.forest.len() as f64)
+    }
+
+    fn classes(&self) -> &[f64] {
+        &self.classes
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![
+            Parameter::new("Trees", Value::Integer(self.trees)),
+            Parameter::new(
+                "Max Features",
+                Value::Choice(&MAX_FEATURES, self.max_features.clone()),
+            ),
+            Parameter::new("Threads", Value::Integer(self.threads)),
+            Parameter::new("Max Depth", Value::Integer(self.options.max_depth)),
+            Parameter::new(
+                "Min Samples Leaf",
+                Value::Integer(self.options.min_samples_leaf),
+            ),
+            Parameter::new(
+                "Criterion",
+                Value::Choice(&tree::CRITERIA, self.options.criterion.clone()),
+            ),
+        ]
+    }
+
+    fn fitted_metrics(&self) -> Vec<(&'static str, f64)> {
+        match self.out_of_bag_accuracy {
+            Some(accuracy) => vec![("OOB Accuracy", accuracy)],
+            None => Vec::new(),
+        }
+    }
+}
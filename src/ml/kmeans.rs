@@ -0,0 +1,153 @@
+use super::seeded_rng;
+use ndarray::prelude::*;
+use rand::prelude::*;
+
+#[derive(Debug, Clone, Copy)]
+pub struct KMeansOptions {
+    pub k: usize,
+    pub max_iterations: usize,
+    // the run with the lowest inertia out of this many is kept
+    pub restarts: usize,
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Clustering {
+    pub centroids: Array2<f64>,
+    pub labels: Vec<usize>,
+    // sum of squared distances from every row to its centroid
+    pub inertia: f64,
+}
+
+impl Clustering {
+    pub fn sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.centroids.nrows()];
+        for &label in self.labels.iter() {
+            sizes[label] += 1;
+        }
+        sizes
+    }
+}
+
+fn squared_distance(a: ArrayView1<f64>, b: ArrayView1<f64>) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+// Index and squared distance of the closest centroid.
+fn closest(sample: ArrayView1<f64>, centroids: &Array2<f64>) -> (usize, f64) {
+    centroids
+        .outer_iter()
+        .map(|centroid| squared_distance(sample, centroid))
+        .enumerate()
+        .fold((0, f64::INFINITY), |acc, (idx, distance)| {
+            if distance < acc.1 {
+                (idx, distance)
+            } else {
+                acc
+            }
+        })
+}
+
+// k-means++: the first centroid is a random row, every next one is picked
+// with a probability proportional to its squared distance to the closest
+// centroid so far.
+fn initial_centroids(data: &Array2<f64>, k: usize, rng: &mut StdRng) -> Array2<f64> {
+    let mut centroids = Array2::zeros([k, data.ncols()]);
+    centroids
+        .row_mut(0)
+        .assign(&data.row(rng.gen_range(0..data.nrows())));
+
+    for idx in 1..k {
+        let chosen = centroids.slice(s![..idx, ..]).to_owned();
+        let distances: Vec<f64> = data
+            .outer_iter()
+            .map(|sample| closest(sample, &chosen).1)
+            .collect();
+        let total: f64 = distances.iter().sum();
+
+        let row = if total == 0. {
+            rng.gen_range(0..data.nrows())
+        } else {
+            let mut target = rng.gen::<f64>() * total;
+            distances
+                .iter()
+                .position(|&distance| {
+                    target -= distance;
+                    target <= 0.
+                })
+                .unwrap_or(data.nrows() - 1)
+        };
+        centroids.row_mut(idx).assign(&data.row(row));
+    }
+
+    centroids
+}
+
+fn run(data: &Array2<f64>, k: usize, max_iterations: usize, rng: &mut StdRng) -> Clustering {
+    let mut centroids = initial_centroids(data, k, rng);
+    let mut labels = vec![0; data.nrows()];
+
+    for iteration in 0..max_iterations {
+        let mut changed = false;
+        for (row, sample) in data.outer_iter().enumerate() {
+            let (label, _) = closest(sample, &centroids);
+            if label != labels[row] {
+                labels[row] = label;
+                changed = true;
+            }
+        }
+        if !changed && iteration > 0 {
+            break;
+        }
+
+        let mut sums = Array2::zeros([k, data.ncols()]);
+        let mut counts = vec![0; k];
+        for (sample, &label) in data.outer_iter().zip(labels.iter()) {
+            let mut sum = sums.row_mut(label);
+            sum += &sample;
+            counts[label] += 1;
+        }
+        // an empty cluster keeps its old centroid
+        for (label, &count) in counts.iter().enumerate() {
+            if count > 0 {
+                let mean = sums.row(label).mapv(|x| x / count as f64);
+                centroids.row_mut(label).assign(&mean);
+            }
+        }
+    }
+
+    let inertia = data
+        .outer_iter()
+        .zip(labels.iter())
+        .map(|(sample, &label)| squared_distance(sample, centroids.row(label)))
+        .sum();
+
+    Clustering {
+        centroids,
+        labels,
+        inertia,
+    }
+}
+
+pub fn kmeans(data: &Array2<f64>, options: &KMeansOptions) -> Clustering {
+    let k = options.k.max(1).min(data.nrows());
+    let mut rng = seeded_rng(options.seed);
+
+    (0..options.restarts.max(1))
+        .map(|_| run(data, k, options.max_iterations, &mut rng))
+        .fold(None, |best: Option<Clustering>, clustering| match best {
+            Some(best) if best.inertia <= clustering.inertia => Some(best),
+            _ => Some(clustering),
+        })
+        .unwrap()
+}
+
+// Inertia of the best clustering for every k from 1 to `max_k`.
+pub fn elbow(data: &Array2<f64>, max_k: usize, options: &KMeansOptions) -> Vec<(usize, f64)> {
+    (1..=max_k.min(data.nrows()))
+        .map(|k| {
+            let options = KMeansOptions { k, ..*options };
+            (k, kmeans(data, &options).inertia)
+        })
+        .collect()
+}
@@ -0,0 +1,110 @@
+use super::model::{Model, Parameter, Settings, Value};
+use super::{classes, features_and_target};
+use ndarray::prelude::*;
+
+const DISTANCES: [&str; 3] = ["Euclidean", "Manhattan", "Cosine"];
+const WEIGHTINGS: [&str; 2] = ["Uniform", "Distance"];
+
+fn distance(metric: &str, a: ArrayView1<f64>, b: ArrayView1<f64>) -> f64 {
+    match metric {
+        "Euclidean" => (&a - &b).mapv(|z| z * z).sum().sqrt(),
+        "Manhattan" => (&a - &b).mapv(f64::abs).sum(),
+        "Cosine" => {
+            let norms = a.dot(&a).sqrt() * b.dot(&b).sqrt();
+            if norms == 0. {
+                1.
+            } else {
+                1. - a.dot(&b) / norms
+            }
+        }
+        _ => panic!("unknown distance {}", metric),
+    }
+}
+
+// Keeps the training set around and lets the `k` closest samples vote, with
+// distance weighting closer neighbours get a bigger say.
+pub struct KNearestNeighbors {
+    k: usize,
+    distance: String,
+    weighting: String,
+    features: Array2<f64>,
+    targets: Array1<f64>,
+    classes: Vec<f64>,
+}
+
+impl KNearestNeighbors {
+    pub fn new(settings: &Settings) -> Self {
+        KNearestNeighbors {
+            k: settings.integer("K", 5),
+            distance: settings.choice("Distance", "Euclidean"),
+            weighting: settings.choice("Weighting", "Uniform"),
+            features: Array2::zeros([0, 0]),
+            targets: Array1::zeros(0),
+            classes: Vec::new(),
+        }
+    }
+}
+
+impl Model for KNearestNeighbors {
+    fn fit(&mut self, train_set: &Array2<f64>, _validation_set: Option<&Array2<f64>>) {
+        let (features, targets) = features_and_target(train_set);
+
+        self.classes = classes(&targets);
+        self.features = features;
+        self.targets = targets;
+    }
+
+    fn predict_proba(&self, features: &Array2<f64>) -> Option<Array2<f64>> {
+        let k = self.k.max(1).min(self.targets.len());
+        let mut probabilities = Array2::zeros([features.nrows(), self.classes.len()]);
+
+        for (row, sample) in features.outer_iter().enumerate() {
+            let mut neighbours: Vec<(f64, f64)> = self
+                .features
+                .outer_iter()
+                .zip(self.targets.iter())
+                .map(|(other, &target)| (distance(&self.distance, sample, other), target))
+                .collect();
+            neighbours.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("Tried to compare a NaN"));
+
+            // an exact match outweighs everything else when weighting by distance
+            let exact = neighbours[0].0 == 0.;
+            for &(distance, target) in neighbours.iter().take(k) {
+                let weight = match self.weighting.as_str() {
+                    "Distance" if exact => {
+                        if distance == 0. {
+                            1.
+                        } else {
+                            0.
+                        }
+                    }
+                    "Distance" => 1. / distance,
+                    _ => 1.,
+                };
+                let class = self.classes.iter().position(|&c| c == target).unwrap();
+                probabilities[[row, class]] += weight;
+            }
+
+            let mut row = probabilities.row_mut(row);
+            let total = row.sum();
+            row.mapv_inplace(|p| p / total);
+        }
+
+        Some(probabilities)
+    }
+
+    fn classes(&self) -> &[f64] {
+        &self.classes
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![
+            Parameter::new("K", Value::Integer(self.k)),
+            Parameter::new("Distance", Value::Choice(&DISTANCES, self.distance.clone())),
+            Parameter::new(
+                "Weighting",
+                Value::Choice(&WEIGHTINGS, self.weighting.clone()),
+            ),
+        ]
+    }
+}
@@ -0,0 +1,204 @@
+use super::descent::GradientDescent;
+use super::model::{Model, Parameter, Settings, Value};
+use super::regularization::Regularization;
+use super::{features_and_target, History};
+use ndarray::prelude::*;
+
+const SOLVERS: [&str; 2] = ["Gradient Descent", "Normal Equation"];
+
+// Half the mean squared error, so the gradient carries no factor of two.
+fn squared_error(y_head: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    (y_head - y).mapv(|z| z * z).sum() / (2. * y.ncols() as f64)
+}
+
+fn forward_backward(
+    parameters: &[Array2<f64>],
+    x_train: &Array2<f64>,
+    y_train: &Array2<f64>,
+    regularization: &Regularization,
+) -> (f64, Vec<Array2<f64>>) {
+    let (weights, bias) = (&parameters[0], &parameters[1]);
+
+    // forward
+    let y_head: Array2<f64> = weights.t().dot(x_train) + bias;
+    let cost = squared_error(&y_head, y_train) + regularization.cost(weights);
+
+    // backward
+    let d_weights = (x_train.dot(&(&y_head - y_train).t())).mapv(|z| z / x_train.ncols() as f64)
+        + regularization.gradient(weights);
+    let d_bias = (&y_head - y_train)
+        .sum_axis(Axis(1))
+        .insert_axis(Axis(1))
+        .mapv(|z| z / x_train.ncols() as f64);
+
+    (cost, vec![d_weights, d_bias])
+}
+
+// Solves `a * x = b` with Gaussian elimination and partial pivoting, None
+// when `a` is singular.
+fn solve(a: Array2<f64>, b: Array1<f64>) -> Option<Array1<f64>> {
+    let n = b.len();
+    let mut a = a;
+    let mut b = b;
+
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|&i, &j| {
+                a[[i, column]]
+                    .abs()
+                    .partial_cmp(&a[[j, column]].abs())
+                    .unwrap()
+            })
+            .unwrap();
+        if a[[pivot, column]].abs() < 1e-12 {
+            return None;
+        }
+        if pivot != column {
+            for k in 0..n {
+                a.swap([pivot, k], [column, k]);
+            }
+            b.swap(pivot, column);
+        }
+
+        for row in column + 1..n {
+            let factor = a[[row, column]] / a[[column, column]];
+            for k in column..n {
+                a[[row, k]] -= factor * a[[column, k]];
+            }
+            b[row] -= factor * b[column];
+        }
+    }
+
+    let mut x = Array1::zeros(n);
+    for row in (0..n).rev() {
+        let rest: f64 = (row + 1..n).map(|k| a[[row, k]] * x[k]).sum();
+        x[row] = (b[row] - rest) / a[[row, row]];
+    }
+    Some(x)
+}
+
+// Predicts a continuous target. The normal equation solves for the weights
+// directly, it only takes the L2 part of the regularization into account.
+pub struct LinearRegression {
+    solver: String,
+    descent: GradientDescent,
+    weights: Array2<f64>,
+    bias: f64,
+    history: Option<History>,
+}
+
+impl LinearRegression {
+    pub fn new(settings: &Settings) -> Self {
+        LinearRegression {
+            solver: settings.choice("Solver", "Gradient Descent"),
+            descent: GradientDescent::new(settings, 0.1, 100),
+            weights: Array2::zeros([0, 0]),
+            bias: 0.,
+            history: None,
+        }
+    }
+
+    // The bias followed by the weights, None when the system has no unique
+    // solution.
+    fn normal_equation(
+        &self,
+        features: &Array2<f64>,
+        targets: &Array1<f64>,
+    ) -> Option<Array1<f64>> {
+        let ones = Array2::ones([features.nrows(), 1]);
+        let x = ndarray::stack(Axis(1), &[ones.view(), features.view()]).unwrap();
+
+        let regularization = self.descent.options.regularization;
+        let mut gram = x.t().dot(&x);
+        let samples = features.nrows() as f64;
+        // the bias in the first row stays unpenalized
+        for i in 1..gram.nrows() {
+            gram[[i, i]] += samples * regularization.strength * (1. - regularization.l1_ratio);
+        }
+        // plus a tiny ridge relative to the scale of the features, so collinear
+        // features like a constant or duplicated column still have a solution
+        let features_count = gram.nrows() - 1;
+        let scale =
+            (1..gram.nrows()).map(|i| gram[[i, i]]).sum::<f64>() / features_count.max(1) as f64;
+        for i in 1..gram.nrows() {
+            gram[[i, i]] += 1e-8 * scale.max(1.);
+        }
+
+        solve(gram, x.t().dot(targets))
+    }
+}
+
+impl Model for LinearRegression {
+    fn fit(&mut self, train_set: &Array2<f64>, validation_set: Option<&Array2<f64>>) {
+        let (features, targets) = features_and_target(train_set);
+        // falls back to gradient descent when even the ridge leaves the system
+        // singular
+        if self.solver == "Normal Equation" {
+            if let Some(theta) = self.normal_equation(&features, &targets) {
+                self.bias = theta[0];
+                self.weights = theta.slice(s![1..]).to_owned().insert_axis(Axis(1));
+                self.history = None;
+                return;
+            }
+        }
+
+        let train = (features.t().to_owned(), targets.insert_axis(Axis(0)));
+        let validation = validation_set.map(|validation_set| {
+            let (features, targets) = features_and_target(validation_set);
+            (features.t().to_owned(), targets.insert_axis(Axis(0)))
+        });
+        let (train, validation) = self.descent.holdout(train, validation);
+
+        let weights = Array2::zeros([train.0.nrows(), 1]);
+        let bias = Array2::zeros([1, 1]);
+
+        let regularization = self.descent.options.regularization;
+        let (history, parameters) = self.descent.train(
+            vec![weights, bias],
+            train,
+            validation,
+            &|parameters, x, y| forward_backward(parameters, x, y, &regularization),
+            &|parameters, x, y| squared_error(&(parameters[0].t().dot(x) + &parameters[1]), y),
+        );
+
+        self.history = Some(history);
+        self.weights = parameters[0].clone();
+        self.bias = parameters[1][[0, 0]];
+    }
+
+    fn predict_proba(&self, _features: &Array2<f64>) -> Option<Array2<f64>> {
+        None
+    }
+
+    fn classes(&self) -> &[f64] {
+        &[]
+    }
+
+    fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let mut parameters = vec![Parameter::new(
+            "Solver",
+            Value::Choice(&SOLVERS, self.solver.clone()),
+        )];
+        for parameter in self.descent.parameters() {
+            parameters.push(match parameter.name {
+                "Regularization" | "Strength" | "L1 Ratio" => parameter,
+                _ if parameter.enabled_when.is_none() => {
+                    parameter.enabled_when("Solver", &["Gradient Descent"])
+                }
+                _ => parameter,
+            });
+        }
+        parameters
+    }
+
+    fn predict(&self, features: &Array2<f64>) -> Array1<f64> {
+        features
+            .dot(&self.weights)
+            .column(0)
+            .mapv(|z| z + self.bias)
+    }
+}
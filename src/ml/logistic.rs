@@ -0,0 +1,126 @@
+use super::descent::GradientDescent;
+use super::model::{Model, Parameter, Settings};
+use super::regularization::Regularization;
+use super::{
+    class_probabilities, classes, encode, features_and_target, sigmoid, softmax_rows, History,
+};
+use ndarray::prelude::*;
+
+// A single output row is a binary problem and uses the sigmoid, anything wider
+// is a multinomial problem with one row per class.
+pub fn activate(z: Array2<f64>) -> Array2<f64> {
+    if z.nrows() == 1 {
+        z.mapv(sigmoid)
+    } else {
+        softmax_rows(z.reversed_axes()).reversed_axes()
+    }
+}
+
+pub fn cross_entropy(y_head: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    let loss = if y_head.nrows() == 1 {
+        (y * &(y_head.mapv(|z| z.ln())) + &((y.mapv(|z| 1. - z)) * &y_head.mapv(|z| (1. - z).ln())))
+            .mapv(|z| -z)
+    } else {
+        (y * &(y_head.mapv(|z| z.ln()))).mapv(|z| -z)
+    };
+
+    loss.sum() / y.ncols() as f64
+}
+
+fn forward_backward(
+    parameters: &[Array2<f64>],
+    x_train: &Array2<f64>,
+    y_train: &Array2<f64>,
+    regularization: &Regularization,
+) -> (f64, Vec<Array2<f64>>) {
+    let (weights, bias) = (&parameters[0], &parameters[1]);
+
+    // forward
+    let y_head: Array2<f64> = activate(weights.t().dot(x_train) + bias);
+    let cost = cross_entropy(&y_head, y_train) + regularization.cost(weights);
+
+    // backward
+    let d_weights = (x_train.dot(&(&y_head - y_train).t())).mapv(|z| z / x_train.ncols() as f64)
+        + regularization.gradient(weights);
+    let d_bias = (&y_head - y_train)
+        .sum_axis(Axis(1))
+        .insert_axis(Axis(1))
+        .mapv(|z| z / x_train.ncols() as f64);
+
+    (cost, vec![d_weights, d_bias])
+}
+
+pub struct LogisticRegression {
+    descent: GradientDescent,
+    weights: Array2<f64>,
+    bias: Array2<f64>,
+    classes: Vec<f64>,
+    history: History,
+}
+
+impl LogisticRegression {
+    pub fn new(settings: &Settings) -> Self {
+        LogisticRegression {
+            descent: GradientDescent::new(settings, 1., 100),
+            weights: Array2::zeros([0, 0]),
+            bias: Array2::zeros([0, 0]),
+            classes: Vec::new(),
+            history: History::default(),
+        }
+    }
+}
+
+// Targets with more than two classes get one weight vector per class and are
+// trained with softmax.
+impl Model for LogisticRegression {
+    fn fit(&mut self, train_set: &Array2<f64>, validation_set: Option<&Array2<f64>>) {
+        let (features, targets) = features_and_target(train_set);
+
+        self.classes = classes(&targets);
+        let train = (features.t().to_owned(), encode(&targets, &self.classes));
+        let validation = validation_set.map(|validation_set| {
+            let (features, targets) = features_and_target(validation_set);
+            (features.t().to_owned(), encode(&targets, &self.classes))
+        });
+        let (train, validation) = self.descent.holdout(train, validation);
+
+        let weights = Array2::from_elem([train.0.nrows(), train.1.nrows()], 0.01);
+        let bias = Array2::zeros([train.1.nrows(), 1]);
+
+        let regularization = self.descent.options.regularization;
+        let (history, mut parameters) = self.descent.train(
+            vec![weights, bias],
+            train,
+            validation,
+            &|parameters, x, y| forward_backward(parameters, x, y, &regularization),
+            &|parameters, x, y| {
+                cross_entropy(&activate(parameters[0].t().dot(x) + &parameters[1]), y)
+            },
+        );
+
+        self.history = history;
+        self.bias = parameters.pop().unwrap();
+        self.weights = parameters.pop().unwrap();
+    }
+
+    fn predict_proba(&self, features: &Array2<f64>) -> Option<Array2<f64>> {
+        let y_head = activate(self.weights.t().dot(&features.t()) + &self.bias);
+
+        Some(class_probabilities(
+            self.classes.len(),
+            y_head.reversed_axes(),
+        ))
+    }
+
+    fn classes(&self) -> &[f64] {
+        &self.classes
+    }
+
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        self.descent.parameters()
+    }
+}
@@ -0,0 +1,207 @@
+use super::descent::GradientDescent;
+use super::logistic::{activate, cross_entropy};
+use super::model::{Model, Parameter, Settings, Value};
+use super::regularization::Regularization;
+use super::{
+    class_probabilities, classes, encode, features_and_target, seeded_rng, sigmoid, History,
+};
+use ndarray::prelude::*;
+use rand::prelude::*;
+
+const ACTIVATIONS: [&str; 3] = ["ReLU", "Tanh", "Sigmoid"];
+
+fn hidden_activation(name: &str, z: &Array2<f64>) -> Array2<f64> {
+    match name {
+        "ReLU" => z.mapv(|x| x.max(0.)),
+        "Tanh" => z.mapv(f64::tanh),
+        "Sigmoid" => z.mapv(sigmoid),
+        _ => panic!("unknown activation {}", name),
+    }
+}
+
+// Derivative of the activation, written in terms of its output `a`.
+fn hidden_derivative(name: &str, a: &Array2<f64>) -> Array2<f64> {
+    match name {
+        "ReLU" => a.mapv(|x| if x > 0. { 1. } else { 0. }),
+        "Tanh" => a.mapv(|x| 1. - x * x),
+        "Sigmoid" => a.mapv(|x| x * (1. - x)),
+        _ => panic!("unknown activation {}", name),
+    }
+}
+
+// Parameters alternate between the weights (inputs x outputs) and the bias of
+// every layer. Returns the output of every layer, starting with the input.
+fn forward(parameters: &[Array2<f64>], x: &Array2<f64>, activation: &str) -> Vec<Array2<f64>> {
+    let layers = parameters.len() / 2;
+    let mut outputs = vec![x.clone()];
+
+    for layer in 0..layers {
+        let (weights, bias) = (&parameters[2 * layer], &parameters[2 * layer + 1]);
+        let z = weights.t().dot(outputs.last().unwrap()) + bias;
+        outputs.push(if layer + 1 == layers {
+            activate(z)
+        } else {
+            hidden_activation(activation, &z)
+        });
+    }
+
+    outputs
+}
+
+fn forward_backward(
+    parameters: &[Array2<f64>],
+    x_train: &Array2<f64>,
+    y_train: &Array2<f64>,
+    activation: &str,
+    regularization: &Regularization,
+) -> (f64, Vec<Array2<f64>>) {
+    let samples = x_train.ncols() as f64;
+    let layers = parameters.len() / 2;
+
+    // forward
+    let outputs = forward(parameters, x_train, activation);
+    let cost = cross_entropy(outputs.last().unwrap(), y_train)
+        + (0..layers)
+            .map(|layer| regularization.cost(&parameters[2 * layer]))
+            .sum::<f64>();
+
+    // backward, sigmoid and softmax with cross entropy both leave `a - y`
+    let mut gradients = vec![Array2::zeros([0, 0]); parameters.len()];
+    let mut delta = outputs.last().unwrap() - y_train;
+    for layer in (0..layers).rev() {
+        let weights = &parameters[2 * layer];
+        gradients[2 * layer] =
+            outputs[layer].dot(&delta.t()).mapv(|z| z / samples) + regularization.gradient(weights);
+        gradients[2 * layer + 1] = delta
+            .sum_axis(Axis(1))
+            .insert_axis(Axis(1))
+            .mapv(|z| z / samples);
+
+        if layer > 0 {
+            delta = weights.dot(&delta) * hidden_derivative(activation, &outputs[layer]);
+        }
+    }
+
+    (cost, gradients)
+}
+
+// Comma separated sizes of the hidden layers, blank for none.
+fn parse_hidden_layers(text: &str) -> Result<Vec<usize>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|size| !size.is_empty())
+        .map(|size| match size.parse::<usize>() {
+            Ok(size) if size > 0 => Ok(size),
+            _ => Err(format!(
+                "\"{}\" isn't a layer size, separate the sizes with commas",
+                size
+            )),
+        })
+        .collect()
+}
+
+// A fully connected network with the same output layer and loss as the
+// logistic regression.
+pub struct MultilayerPerceptron {
+    // comma separated size of every hidden layer
+    hidden_layers: String,
+    activation: String,
+    descent: GradientDescent,
+    parameters: Vec<Array2<f64>>,
+    classes: Vec<f64>,
+    history: History,
+}
+
+impl MultilayerPerceptron {
+    pub fn new(settings: &Settings) -> Self {
+        MultilayerPerceptron {
+            hidden_layers: settings.text("Hidden Layers", "16, 8"),
+            activation: settings.choice("Activation", "ReLU"),
+            descent: GradientDescent::new(settings, 0.1, 100),
+            parameters: Vec::new(),
+            classes: Vec::new(),
+            history: History::default(),
+        }
+    }
+
+    // Glorot uniform weights and zero biases for the given layer sizes.
+    fn initialize(&self, sizes: &[usize]) -> Vec<Array2<f64>> {
+        let mut rng = seeded_rng(self.descent.options.seed);
+
+        sizes
+            .windows(2)
+            .flat_map(|pair| {
+                let (inputs, outputs) = (pair[0], pair[1]);
+                let limit = (6. / (inputs + outputs) as f64).sqrt();
+                let weights =
+                    Array2::from_shape_fn([inputs, outputs], |_| rng.gen_range(-limit..limit));
+                vec![weights, Array2::zeros([outputs, 1])]
+            })
+            .collect()
+    }
+}
+
+impl Model for MultilayerPerceptron {
+    fn fit(&mut self, train_set: &Array2<f64>, validation_set: Option<&Array2<f64>>) {
+        let (features, targets) = features_and_target(train_set);
+
+        self.classes = classes(&targets);
+        let train = (features.t().to_owned(), encode(&targets, &self.classes));
+        let validation = validation_set.map(|validation_set| {
+            let (features, targets) = features_and_target(validation_set);
+            (features.t().to_owned(), encode(&targets, &self.classes))
+        });
+        let (train, validation) = self.descent.holdout(train, validation);
+
+        let mut sizes = vec![train.0.nrows()];
+        sizes.extend(parse_hidden_layers(&self.hidden_layers).unwrap());
+        sizes.push(train.1.nrows());
+
+        let activation = self.activation.clone();
+        let regularization = self.descent.options.regularization;
+        let (history, parameters) = self.descent.train(
+            self.initialize(&sizes),
+            train,
+            validation,
+            &|parameters, x, y| forward_backward(parameters, x, y, &activation, &regularization),
+            &|parameters, x, y| {
+                cross_entropy(forward(parameters, x, &activation).last().unwrap(), y)
+            },
+        );
+
+        self.history = history;
+        self.parameters = parameters;
+    }
+
+    fn predict_proba(&self, features: &Array2<f64>) -> Option<Array2<f64>> {
+        let y_head = forward(&self.parameters, &features.t().to_owned(), &self.activation)
+            .pop()
+            .unwrap();
+
+        Some(class_probabilities(
+            self.classes.len(),
+            y_head.reversed_axes(),
+        ))
+    }
+
+    fn classes(&self) -> &[f64] {
+        &self.classes
+    }
+
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let mut parameters = vec![
+            Parameter::new("Hidden Layers", Value::Text(self.hidden_layers.clone()))
+                .checked_with(|text| parse_hidden_layers(text).map(|_| ())),
+            Parameter::new(
+                "Activation",
+                Value::Choice(&ACTIVATIONS, self.activation.clone()),
+            ),
+        ];
+        parameters.extend(self.descent.parameters());
+        parameters
+    }
+}
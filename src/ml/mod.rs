@@ -0,0 +1,625 @@
+pub mod bayes;
+pub mod boosting;
+pub mod confusion;
+pub mod curves;
+pub mod descent;
+pub mod forest;
+pub mod kmeans;
+pub mod knn;
+pub mod linear;
+pub mod logistic;
+pub mod mlp;
+pub mod model;
+pub mod optimizer;
+pub mod pca;
+pub mod regularization;
+pub mod svm;
+pub mod tree;
+
+use confusion::ConfusionMatrix;
+use model::{Algorithm, Model};
+use ndarray::prelude::*;
+use polars::prelude::*;
+use rand::prelude::*;
+use regularization::Regularization;
+
+pub fn algorithms() -> Vec<Algorithm> {
+    vec![
+        Algorithm {
+            name: "Logistic Regression",
+            create: |settings| Box::new(logistic::LogisticRegression::new(settings)),
+        },
+        Algorithm {
+            name: "Linear Regression",
+            create: |settings| Box::new(linear::LinearRegression::new(settings)),
+        },
+        Algorithm {
+            name: "k-Nearest Neighbors",
+            create: |settings| Box::new(knn::KNearestNeighbors::new(settings)),
+        },
+        Algorithm {
+            name: "Decision Tree",
+            create: |settings| Box::new(tree::DecisionTree::new(settings)),
+        },
+        Algorithm {
+            name: "Random Forest",
+            create: |settings| Box::new(forest::RandomForest::new(settings)),
+        },
+        Algorithm {
+            name: "Gradient Boosting",
+            create: |settings| Box::new(boosting::GradientBoosting::new(settings)),
+        },
+        Algorithm {
+            name: "Gaussian Naive Bayes",
+            create: |settings| Box::new(bayes::GaussianNaiveBayes::new(settings)),
+        },
+        Algorithm {
+            name: "Linear SVM",
+            create: |settings| Box::new(svm::LinearSvm::new(settings)),
+        },
+        Algorithm {
+            name: "Multilayer Perceptron",
+            create: |settings| Box::new(mlp::MultilayerPerceptron::new(settings)),
+        },
+    ]
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TrainOptions {
+    pub iterations: usize,
+    // `None` trains on the whole set every iteration, otherwise the rows are
+    // reshuffled each epoch and a cost is recorded for every batch
+    pub batch_size: Option<usize>,
+    pub regularization: Regularization,
+    pub early_stopping: Option<EarlyStopping>,
+    pub seed: Option<u64>,
+}
+
+// Stops once the validation cost hasn't improved for `patience` epochs. When
+// no validation set is passed to `fit` the last `validation_ratio` of the
+// training rows is held back instead.
+#[derive(Debug, Clone, Copy)]
+pub struct EarlyStopping {
+    pub validation_ratio: f64,
+    pub patience: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    pub costs: Vec<f64>,
+    // validation cost after every epoch paired with the number of training
+    // costs recorded up to that point, so both share the same x axis
+    pub validation_costs: Vec<(usize, f64)>,
+    pub best: Option<usize>,
+    pub stopped: Option<usize>,
+}
+
+// The same seed always produces the same shuffles, `None` seeds from the OS.
+fn seeded_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+// Fisher-Yates: every item is swapped with a uniformly chosen item at or
+// before it, walking from the last item down.
+fn random_shuffle<T>(items: &mut [T], rng: &mut StdRng) {
+    for i in (1..items.len()).rev() {
+        let j = rng.gen_range(0..=i);
+        items.swap(i, j);
+    }
+}
+
+// Rows whose last column holds each class, in file order.
+fn rows_by_class(matrix: &Array2<f64>) -> Vec<Vec<usize>> {
+    let (_, targets) = features_and_target(matrix);
+    classes(&targets)
+        .into_iter()
+        .map(|class| {
+            (0..matrix.nrows())
+                .filter(|&row| targets[row] == class)
+                .collect()
+        })
+        .collect()
+}
+
+// Targets with more distinct values than this are taken as continuous, they
+// aren't stratified and their class counts aren't listed.
+pub const MAX_CLASSES: usize = 10;
+
+pub fn is_categorical(set: &Array2<f64>) -> bool {
+    let (_, targets) = features_and_target(set);
+    classes(&targets).len() <= MAX_CLASSES
+}
+
+pub fn target_is_categorical(df: &DataFrame) -> bool {
+    is_categorical(&df.to_ndarray::<Float64Type>().unwrap())
+}
+
+pub fn class_counts(set: &Array2<f64>) -> Vec<(f64, usize)> {
+    let (_, targets) = features_and_target(set);
+    classes(&targets)
+        .into_iter()
+        .map(|class| (class, targets.iter().filter(|&&y| y == class).count()))
+        .collect()
+}
+
+// Whatever is left after the train and validation ratios becomes the test set.
+#[derive(Debug, Clone, Copy)]
+pub struct SplitOptions {
+    pub train_ratio: f64,
+    pub validation_ratio: f64,
+    // keeps the rows in file order, for data where the ordering matters
+    pub shuffle: bool,
+    // cuts every class by the ratios separately so all partitions keep the
+    // class proportions of the whole data
+    pub stratified: bool,
+    pub seed: Option<u64>,
+}
+
+// How many rows of every group go before a cut at `ratio`. Every group gets
+// the floor of its share and the rows still missing from the overall total go
+// to the largest remainders, so groups of a single row aren't all rounded
+// down to nothing.
+fn apportion(sizes: &[usize], ratio: f64) -> Vec<usize> {
+    // the epsilon keeps ratios like 0.57 * 100 from flooring to 56
+    let cut = |rows: usize| (((ratio * rows as f64) + 1e-9).floor() as usize).min(rows);
+    let mut counts: Vec<usize> = sizes.iter().map(|&size| cut(size)).collect();
+    let remainders: Vec<f64> = sizes
+        .iter()
+        .zip(counts.iter())
+        .map(|(&size, &count)| ratio * size as f64 - count as f64)
+        .collect();
+
+    let missing = cut(sizes.iter().sum()).saturating_sub(counts.iter().sum());
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|&a, &b| {
+        remainders[b]
+            .partial_cmp(&remainders[a])
+            .expect("Tried to compare a NaN")
+    });
+    for group in order.into_iter().take(missing) {
+        counts[group] += 1;
+    }
+
+    counts
+}
+
+pub fn split(
+    df: &DataFrame,
+    options: &SplitOptions,
+) -> (Array2<f64>, Option<Array2<f64>>, Array2<f64>) {
+    let matrix: Array2<f64> = df.to_ndarray::<Float64Type>().unwrap();
+    let mut rng = seeded_rng(options.seed);
+
+    // a continuous target would make a group of every row
+    let stratified = options.stratified && is_categorical(&matrix);
+    let mut groups = if stratified {
+        rows_by_class(&matrix)
+    } else {
+        vec![(0..matrix.nrows()).collect()]
+    };
+
+    let sizes: Vec<usize> = groups.iter().map(Vec::len).collect();
+    let train_ends = apportion(&sizes, options.train_ratio);
+    let validation_ends = apportion(&sizes, options.train_ratio + options.validation_ratio);
+
+    let mut partitions: [Vec<usize>; 3] = [Vec::new(), Vec::new(), Vec::new()];
+    for (idx, group) in groups.iter_mut().enumerate() {
+        if options.shuffle {
+            random_shuffle(group, &mut rng);
+        }
+
+        let train_end = train_ends[idx];
+        let validation_end = validation_ends[idx].max(train_end);
+
+        partitions[0].extend_from_slice(&group[..train_end]);
+        partitions[1].extend_from_slice(&group[train_end..validation_end]);
+        partitions[2].extend_from_slice(&group[validation_end..]);
+    }
+
+    // stratifying leaves the rows grouped by class, mix them back together
+    if stratified {
+        for partition in partitions.iter_mut() {
+            if options.shuffle {
+                random_shuffle(partition, &mut rng);
+            } else {
+                partition.sort_unstable();
+            }
+        }
+    }
+
+    let [train_rows, validation_rows, test_rows] = partitions;
+    (
+        matrix.select(Axis(0), &train_rows),
+        if validation_rows.is_empty() {
+            None
+        } else {
+            Some(matrix.select(Axis(0), &validation_rows))
+        },
+        matrix.select(Axis(0), &test_rows),
+    )
+}
+
+// Splits a set into its feature rows and the target in the last column.
+fn features_and_target(set: &Array2<f64>) -> (Array2<f64>, Array1<f64>) {
+    (
+        set.slice(s![.., ..-1]).to_owned(),
+        set.column(set.ncols() - 1).to_owned(),
+    )
+}
+
+// Deals the row indices out into `folds` groups. Stratified folds deal every
+// class separately so each fold keeps the overall class proportions.
+fn fold_indices(
+    matrix: &Array2<f64>,
+    folds: usize,
+    stratified: bool,
+    seed: Option<u64>,
+) -> Vec<Vec<usize>> {
+    let mut rng = seeded_rng(seed);
+    let mut fold_indices = vec![Vec::new(); folds];
+
+    let mut groups: Vec<Vec<usize>> = if stratified && is_categorical(matrix) {
+        rows_by_class(matrix)
+    } else {
+        vec![(0..matrix.nrows()).collect()]
+    };
+
+    let mut position = 0;
+    for group in groups.iter_mut() {
+        random_shuffle(group, &mut rng);
+        for &row in group.iter() {
+            fold_indices[position % folds].push(row);
+            position += 1;
+        }
+    }
+
+    fold_indices
+}
+
+// Trains a fresh model on every combination of `folds - 1` folds and returns
+// the metrics on each held out fold. Needs at least two folds and no more
+// folds than rows so no training or test set is empty.
+pub fn cross_validate(
+    df: &DataFrame,
+    folds: usize,
+    stratified: bool,
+    seed: Option<u64>,
+    create_model: &dyn Fn() -> Box<dyn Model>,
+) -> Vec<Vec<(&'static str, f64)>> {
+    let matrix: Array2<f64> = df.to_ndarray::<Float64Type>().unwrap();
+    assert!(
+        folds >= 2 && folds <= matrix.nrows(),
+        "cross validation needs between 2 and {} folds, got {}",
+        matrix.nrows(),
+        folds
+    );
+    let fold_indices = fold_indices(&matrix, folds, stratified, seed);
+
+    fold_indices
+        .iter()
+        .enumerate()
+        .map(|(fold, test_indices)| {
+            let train_indices: Vec<usize> = fold_indices
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != fold)
+                .flat_map(|(_, indices)| indices.iter().cloned())
+                .collect();
+            let train_set = matrix.select(Axis(0), &train_indices);
+            let test_set = matrix.select(Axis(0), test_indices);
+
+            let mut model = create_model();
+            model.fit(&train_set, None);
+            let (_, prediction) = make_prediction(&test_set, model.as_ref());
+
+            prediction.metrics(None, "Macro")
+        })
+        .collect()
+}
+
+pub fn mean_and_std(values: &[f64]) -> (f64, f64) {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / values.len() as f64;
+
+    (mean, variance.sqrt())
+}
+
+fn classes(y: &Array1<f64>) -> Vec<f64> {
+    let mut classes: Vec<f64> = y.iter().cloned().collect();
+    classes.sort_by(|a, b| a.partial_cmp(b).expect("Tried to compare a NaN"));
+    classes.dedup();
+    classes
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1. / (1. + (-z).exp())
+}
+
+// Softmax across every row, shifted by the row maximum so it can't overflow.
+fn softmax_rows(scores: Array2<f64>) -> Array2<f64> {
+    let mut scores = scores;
+    for mut row in scores.outer_iter_mut() {
+        let max = row.fold(f64::NEG_INFINITY, |acc, &x| acc.max(x));
+        row.mapv_inplace(|x| (x - max).exp());
+        let sum = row.sum();
+        row.mapv_inplace(|x| x / sum);
+    }
+    scores
+}
+
+// Spreads the output of a classifier over one column per class, binary
+// targets only come with the probability of the positive class.
+fn class_probabilities(n_classes: usize, probabilities: Array2<f64>) -> Array2<f64> {
+    match n_classes {
+        1 => Array2::ones([probabilities.nrows(), 1]),
+        2 => ndarray::stack(
+            Axis(1),
+            &[probabilities.mapv(|p| 1. - p).view(), probabilities.view()],
+        )
+        .unwrap(),
+        _ => probabilities,
+    }
+}
+
+// Binary targets become a single row of zeros and ones, wider targets are one
+// hot encoded with a row per class and a column per sample.
+fn encode(y: &Array1<f64>, classes: &[f64]) -> Array2<f64> {
+    if classes.len() <= 2 {
+        let positive = *classes.last().unwrap();
+        return y
+            .mapv(|z| if z == positive { 1. } else { 0. })
+            .insert_axis(Axis(0));
+    }
+
+    let mut encoded = Array2::zeros([classes.len(), y.len()]);
+    for (idx, label) in y.iter().enumerate() {
+        if let Some(class) = classes.iter().position(|c| c == label) {
+            encoded[[class, idx]] = 1.;
+        }
+    }
+    encoded
+}
+
+// Log-loss and Brier score of the predicted probabilities, `probabilities` has
+// one column per class. The Brier score of binary targets only looks at the
+// positive class so it stays between 0 and 1.
+fn probability_metrics(
+    y_test: &Array1<f64>,
+    probabilities: &Array2<f64>,
+    classes: &[f64],
+) -> Vec<(&'static str, f64)> {
+    let encoded = Array2::from_shape_fn([y_test.len(), classes.len()], |(row, class)| {
+        if y_test[row] == classes[class] {
+            1.
+        } else {
+            0.
+        }
+    });
+
+    // subtracted from 0 rather than negated, a perfect fit would show as -0
+    let log_loss =
+        0. - (&encoded * &probabilities.mapv(|p| p.max(1e-15).ln())).sum() / y_test.len() as f64;
+    let squared_errors = (probabilities - &encoded).mapv(|z| z * z);
+    let brier_score = if classes.len() == 2 {
+        squared_errors.column(1).mean().unwrap()
+    } else {
+        squared_errors.sum() / y_test.len() as f64
+    };
+
+    vec![("Log-Loss", log_loss), ("Brier Score", brier_score)]
+}
+
+fn regression_metrics(y_test: &Array1<f64>, y_pred: &Array1<f64>) -> Vec<(&'static str, f64)> {
+    let residuals = y_pred - y_test;
+    let mse = residuals.mapv(|z| z * z).mean().unwrap();
+    let mae = residuals.mapv(f64::abs).mean().unwrap();
+
+    let mean = y_test.mean().unwrap();
+    let total = y_test.mapv(|z| (z - mean).powi(2)).sum();
+    let r2 = 1. - residuals.mapv(|z| z * z).sum() / total;
+
+    vec![("MSE", mse), ("RMSE", mse.sqrt()), ("MAE", mae), ("R²", r2)]
+}
+
+// The model outputs on a test set, kept around so it can be scored again at a
+// different decision threshold without predicting again.
+#[derive(Debug, Clone)]
+pub struct Prediction {
+    pub actual: Array1<f64>,
+    pub predicted: Array1<f64>,
+    // one column per class, `None` for regression models
+    pub probabilities: Option<Array2<f64>>,
+    pub classes: Vec<f64>,
+    pub fitted_metrics: Vec<(&'static str, f64)>,
+}
+
+impl Prediction {
+    pub fn is_binary(&self) -> bool {
+        self.probabilities.is_some() && self.classes.len() == 2
+    }
+
+    // Probability of the positive (last) class, for binary targets only.
+    pub fn scores(&self) -> Option<ArrayView1<'_, f64>> {
+        if self.is_binary() {
+            self.probabilities.as_ref().map(|p| p.column(1))
+        } else {
+            None
+        }
+    }
+
+    // Binary targets are predicted positive once the positive probability
+    // reaches `threshold`, other targets ignore it.
+    pub fn predicted_at(&self, threshold: f64) -> Array1<f64> {
+        match self.scores() {
+            Some(scores) => scores.mapv(|p| {
+                if p >= threshold {
+                    self.classes[1]
+                } else {
+                    self.classes[0]
+                }
+            }),
+            None => self.predicted.clone(),
+        }
+    }
+
+    // `None` keeps the model's own predictions.
+    pub fn confusion_matrix(&self, threshold: Option<f64>) -> ConfusionMatrix {
+        let predicted = match threshold {
+            Some(threshold) => self.predicted_at(threshold),
+            None => self.predicted.clone(),
+        };
+        ConfusionMatrix::new(&self.actual, &predicted, &self.classes)
+    }
+
+    pub fn roc_curve(&self) -> Option<Vec<curves::RocPoint>> {
+        self.scores()
+            .map(|scores| curves::roc_curve(self.actual.view(), scores, self.classes[1]))
+    }
+
+    pub fn precision_recall_curve(&self) -> Option<Vec<curves::PrPoint>> {
+        self.scores().map(|scores| {
+            curves::precision_recall_curve(self.actual.view(), scores, self.classes[1])
+        })
+    }
+
+    pub fn calibration_curve(&self, bins: usize) -> Option<Vec<curves::CalibrationBin>> {
+        self.scores().map(|scores| {
+            curves::calibration_curve(self.actual.view(), scores, self.classes[1], bins)
+        })
+    }
+
+    // Named metrics at `threshold`, multi-class metrics are combined with
+    // `average`.
+    pub fn metrics(&self, threshold: Option<f64>, average: &str) -> Vec<(&'static str, f64)> {
+        let mut metrics = if self.probabilities.is_some() {
+            self.confusion_matrix(threshold).metrics(average)
+        } else {
+            regression_metrics(&self.actual, &self.predicted)
+        };
+        if let Some(probabilities) = &self.probabilities {
+            metrics.extend(probability_metrics(
+                &self.actual,
+                probabilities,
+                &self.classes,
+            ));
+        }
+        if let Some(roc) = self.roc_curve() {
+            metrics.push(("ROC AUC", curves::roc_auc(&roc)));
+        }
+        if let Some(pr) = self.precision_recall_curve() {
+            metrics.push(("Average Precision", curves::average_precision(&pr)));
+        }
+        metrics.extend(self.fitted_metrics.iter().cloned());
+        metrics
+    }
+}
+
+// Returns the predictions next to the actual values, classifiers also get the
+// probability of the predicted class (of the positive class for binary
+// targets).
+pub fn make_prediction(test_set: &Array2<f64>, model: &dyn Model) -> (DataFrame, Prediction) {
+    let (x_test, y_test) = features_and_target(test_set);
+    let y_pred = model.predict(&x_test);
+
+    let probabilities = model.predict_proba(&x_test);
+
+    let mut columns = vec![
+        Series::new("Actual Values", y_test.to_vec()),
+        Series::new("Predictions", y_pred.to_vec()),
+    ];
+    if let Some(probabilities) = &probabilities {
+        let shown: Vec<f64> = if model.classes().len() == 2 {
+            probabilities.column(1).to_vec()
+        } else {
+            probabilities
+                .outer_iter()
+                .map(|row| row.fold(0., |acc: f64, &p| acc.max(p)))
+                .collect()
+        };
+        columns.push(Series::new("Probability", shown));
+    }
+
+    (
+        DataFrame::new(columns).unwrap(),
+        Prediction {
+            actual: y_test,
+            predicted: y_pred,
+            probabilities,
+            classes: model.classes().to_vec(),
+            fitted_metrics: model.fitted_metrics(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // two features and a target column that is 1 for every fourth row
+    fn data_frame(rows: usize) -> DataFrame {
+        let column = |f: &dyn Fn(usize) -> f64| (0..rows).map(f).collect::<Vec<f64>>();
+        DataFrame::new(vec![
+            Series::new("a", column(&|row| row as f64)),
+            Series::new("b", column(&|row| (row * row) as f64)),
+            Series::new("target", column(&|row| (row % 4 == 0) as usize as f64)),
+        ])
+        .unwrap()
+    }
+
+    fn split_options(stratified: bool, seed: u64) -> SplitOptions {
+        SplitOptions {
+            train_ratio: 0.6,
+            validation_ratio: 0.2,
+            shuffle: true,
+            stratified,
+            seed: Some(seed),
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_split() {
+        let df = data_frame(40);
+        for stratified in [false, true].iter() {
+            let first = split(&df, &split_options(*stratified, 7));
+            let second = split(&df, &split_options(*stratified, 7));
+            assert_eq!(first, second);
+
+            let other = split(&df, &split_options(*stratified, 8));
+            assert_ne!(first, other);
+        }
+    }
+
+    #[test]
+    fn stratified_split_keeps_class_proportions() {
+        // 10 positives and 30 negatives
+        let df = data_frame(40);
+        let (train_set, validation_set, test_set) = split(&df, &split_options(true, 7));
+
+        assert_eq!(class_counts(&train_set), vec![(0., 18), (1., 6)]);
+        assert_eq!(
+            class_counts(&validation_set.unwrap()),
+            vec![(0., 6), (1., 2)]
+        );
+        assert_eq!(class_counts(&test_set), vec![(0., 6), (1., 2)]);
+    }
+
+    #[test]
+    fn continuous_target_is_not_stratified() {
+        // every row has its own target value
+        let df = DataFrame::new(vec![
+            Series::new("a", (0..20).map(|row| row as f64).collect::<Vec<f64>>()),
+            Series::new(
+                "target",
+                (0..20).map(|row| row as f64).collect::<Vec<f64>>(),
+            ),
+        ])
+        .unwrap();
+
+        let stratified = split(&df, &split_options(true, 7));
+        assert_eq!(stratified.0.nrows(), 12);
+        assert_eq!(stratified, split(&df, &split_options(false, 7)));
+    }
+}
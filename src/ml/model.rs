@@ -0,0 +1,149 @@
+use super::tree::Node;
+use super::History;
+use ndarray::prelude::*;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Number(f64),
+    Integer(usize),
+    Text(String),
+    Choice(&'static [&'static str], String),
+    Flag(bool),
+}
+
+// Tells what is wrong with the text of a parameter, if anything.
+pub type Check = fn(&str) -> Result<(), String>;
+
+// Describes one hyperparameter of a model along with its current value. A
+// parameter with `enabled_when` only applies while the named choice (or flag,
+// as "true"/"false") is set to one of the listed values. Text with a format of
+// its own gets a `check`, so mistakes are caught before the model is fitted.
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub value: Value,
+    pub enabled_when: Option<(&'static str, &'static [&'static str])>,
+    pub check: Option<Check>,
+}
+
+impl Parameter {
+    pub fn new(name: &'static str, value: Value) -> Self {
+        Parameter {
+            name,
+            value,
+            enabled_when: None,
+            check: None,
+        }
+    }
+
+    pub fn enabled_when(mut self, name: &'static str, values: &'static [&'static str]) -> Self {
+        self.enabled_when = Some((name, values));
+        self
+    }
+
+    pub fn checked_with(mut self, check: Check) -> Self {
+        self.check = Some(check);
+        self
+    }
+}
+
+// Hyperparameter values read from the model page, models fall back to their
+// own defaults for anything that is missing.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub values: HashMap<&'static str, Value>,
+    pub seed: Option<u64>,
+}
+
+impl Settings {
+    pub fn number(&self, name: &str, default: f64) -> f64 {
+        match self.values.get(name) {
+            Some(Value::Number(x)) => *x,
+            _ => default,
+        }
+    }
+
+    pub fn integer(&self, name: &str, default: usize) -> usize {
+        match self.values.get(name) {
+            Some(Value::Integer(x)) => *x,
+            _ => default,
+        }
+    }
+
+    pub fn text(&self, name: &str, default: &str) -> String {
+        match self.values.get(name) {
+            Some(Value::Text(x)) => x.clone(),
+            _ => default.to_string(),
+        }
+    }
+
+    pub fn choice(&self, name: &str, default: &str) -> String {
+        match self.values.get(name) {
+            Some(Value::Choice(_, x)) => x.clone(),
+            _ => default.to_string(),
+        }
+    }
+
+    pub fn flag(&self, name: &str, default: bool) -> bool {
+        match self.values.get(name) {
+            Some(Value::Flag(x)) => *x,
+            _ => default,
+        }
+    }
+}
+
+// Features are passed with one sample per row and without the target column.
+pub trait Model {
+    fn fit(&mut self, train_set: &Array2<f64>, validation_set: Option<&Array2<f64>>);
+
+    // One column per entry of `classes`, every row sums up to one. None for
+    // models that don't predict classes.
+    fn predict_proba(&self, features: &Array2<f64>) -> Option<Array2<f64>>;
+
+    fn classes(&self) -> &[f64];
+
+    fn history(&self) -> Option<&History> {
+        None
+    }
+
+    fn parameters(&self) -> Vec<Parameter>;
+
+    // Metrics the model works out while fitting, reported next to the test
+    // metrics.
+    fn fitted_metrics(&self) -> Vec<(&'static str, f64)> {
+        Vec::new()
+    }
+
+    // The cost on `set` after every round of training, for models that can
+    // replay their rounds on other data.
+    fn staged_costs(&self, _set: &Array2<f64>) -> Option<Vec<f64>> {
+        None
+    }
+
+    // The learned tree, for models that are a single decision tree.
+    fn tree(&self) -> Option<&Node> {
+        None
+    }
+
+    fn predict(&self, features: &Array2<f64>) -> Array1<f64> {
+        let classes = self.classes();
+        self.predict_proba(features)
+            .expect("models without probabilities predict their values directly")
+            .outer_iter()
+            .map(|probabilities| {
+                let (class, _) = probabilities.iter().enumerate().fold(
+                    (0, f64::NEG_INFINITY),
+                    |acc, (idx, &p)| if p > acc.1 { (idx, p) } else { acc },
+                );
+                classes[class]
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Algorithm {
+    pub name: &'static str,
+    pub create: fn(&Settings) -> Box<dyn Model>,
+}
@@ -0,0 +1,199 @@
+use ndarray::prelude::*;
+
+pub const OPTIMIZERS: [&str; 5] = ["SGD", "Momentum", "Nesterov", "RMSProp", "Adam"];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Hyperparameters {
+    pub learning_rate: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub epsilon: f64,
+}
+
+// `parameters` and `gradients` are matched up by position, stateful
+// optimizers rely on them being passed in the same order on every step.
+pub trait Optimizer {
+    fn step(&mut self, parameters: &mut [&mut Array2<f64>], gradients: &[Array2<f64>]);
+}
+
+pub fn create(name: &str, hyperparameters: Hyperparameters) -> Box<dyn Optimizer> {
+    match name {
+        "SGD" => Box::new(Sgd::new(hyperparameters)),
+        "Momentum" => Box::new(Momentum::new(hyperparameters, false)),
+        "Nesterov" => Box::new(Momentum::new(hyperparameters, true)),
+        "RMSProp" => Box::new(RmsProp::new(hyperparameters)),
+        "Adam" => Box::new(Adam::new(hyperparameters)),
+        _ => panic!("unknown optimizer {}", name),
+    }
+}
+
+fn zeros_like(gradients: &[Array2<f64>]) -> Vec<Array2<f64>> {
+    gradients
+        .iter()
+        .map(|g| Array2::zeros(g.raw_dim()))
+        .collect()
+}
+
+pub struct Sgd {
+    learning_rate: f64,
+}
+
+impl Sgd {
+    pub fn new(hyperparameters: Hyperparameters) -> Self {
+        Sgd {
+            learning_rate: hyperparameters.learning_rate,
+        }
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, parameters: &mut [&mut Array2<f64>], gradients: &[Array2<f64>]) {
+        for (parameter, gradient) in parameters.iter_mut().zip(gradients) {
+            parameter.scaled_add(-self.learning_rate, gradient);
+        }
+    }
+}
+
+pub struct Momentum {
+    learning_rate: f64,
+    momentum: f64,
+    nesterov: bool,
+    velocities: Vec<Array2<f64>>,
+}
+
+impl Momentum {
+    pub fn new(hyperparameters: Hyperparameters, nesterov: bool) -> Self {
+        Momentum {
+            learning_rate: hyperparameters.learning_rate,
+            momentum: hyperparameters.beta1,
+            nesterov,
+            velocities: Vec::new(),
+        }
+    }
+}
+
+impl Optimizer for Momentum {
+    fn step(&mut self, parameters: &mut [&mut Array2<f64>], gradients: &[Array2<f64>]) {
+        if self.velocities.is_empty() {
+            self.velocities = zeros_like(gradients);
+        }
+
+        for ((parameter, gradient), velocity) in parameters
+            .iter_mut()
+            .zip(gradients)
+            .zip(self.velocities.iter_mut())
+        {
+            let previous = velocity.clone();
+            *velocity *= self.momentum;
+            velocity.scaled_add(-self.learning_rate, gradient);
+
+            if self.nesterov {
+                // look-ahead form of Nesterov momentum, it only needs the
+                // gradient at the current parameters
+                parameter.scaled_add(-self.momentum, &previous);
+                parameter.scaled_add(1. + self.momentum, velocity);
+            } else {
+                **parameter += &*velocity;
+            }
+        }
+    }
+}
+
+pub struct RmsProp {
+    learning_rate: f64,
+    decay: f64,
+    epsilon: f64,
+    squares: Vec<Array2<f64>>,
+}
+
+impl RmsProp {
+    pub fn new(hyperparameters: Hyperparameters) -> Self {
+        RmsProp {
+            learning_rate: hyperparameters.learning_rate,
+            decay: hyperparameters.beta2,
+            epsilon: hyperparameters.epsilon,
+            squares: Vec::new(),
+        }
+    }
+}
+
+impl Optimizer for RmsProp {
+    fn step(&mut self, parameters: &mut [&mut Array2<f64>], gradients: &[Array2<f64>]) {
+        if self.squares.is_empty() {
+            self.squares = zeros_like(gradients);
+        }
+
+        for ((parameter, gradient), square) in parameters
+            .iter_mut()
+            .zip(gradients)
+            .zip(self.squares.iter_mut())
+        {
+            *square *= self.decay;
+            square.scaled_add(1. - self.decay, &gradient.mapv(|g| g * g));
+
+            let (learning_rate, epsilon) = (self.learning_rate, self.epsilon);
+            ndarray::Zip::from(&mut **parameter)
+                .and(gradient)
+                .and(&*square)
+                .apply(|p, &g, &s| *p -= learning_rate * g / (s.sqrt() + epsilon));
+        }
+    }
+}
+
+pub struct Adam {
+    learning_rate: f64,
+    beta1: f64,
+    beta2: f64,
+    epsilon: f64,
+    timestep: i32,
+    moments: Vec<Array2<f64>>,
+    squares: Vec<Array2<f64>>,
+}
+
+impl Adam {
+    pub fn new(hyperparameters: Hyperparameters) -> Self {
+        Adam {
+            learning_rate: hyperparameters.learning_rate,
+            beta1: hyperparameters.beta1,
+            beta2: hyperparameters.beta2,
+            epsilon: hyperparameters.epsilon,
+            timestep: 0,
+            moments: Vec::new(),
+            squares: Vec::new(),
+        }
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, parameters: &mut [&mut Array2<f64>], gradients: &[Array2<f64>]) {
+        if self.moments.is_empty() {
+            self.moments = zeros_like(gradients);
+            self.squares = zeros_like(gradients);
+        }
+        self.timestep += 1;
+
+        let moment_correction = 1. - self.beta1.powi(self.timestep);
+        let square_correction = 1. - self.beta2.powi(self.timestep);
+
+        for (((parameter, gradient), moment), square) in parameters
+            .iter_mut()
+            .zip(gradients)
+            .zip(self.moments.iter_mut())
+            .zip(self.squares.iter_mut())
+        {
+            *moment *= self.beta1;
+            moment.scaled_add(1. - self.beta1, gradient);
+            *square *= self.beta2;
+            square.scaled_add(1. - self.beta2, &gradient.mapv(|g| g * g));
+
+            let (learning_rate, epsilon) = (self.learning_rate, self.epsilon);
+            ndarray::Zip::from(&mut **parameter)
+                .and(&*moment)
+                .and(&*square)
+                .apply(|p, &m, &s| {
+                    *p -= learning_rate * (m / moment_correction)
+                        / ((s / square_correction).sqrt() + epsilon)
+                });
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use ndarray::prelude::*;
+
+// Eigenvalues and eigenvectors (as columns) of a symmetric matrix using
+// cyclic Jacobi rotations, sorted by decreasing eigenvalue.
+fn symmetric_eigen(matrix: &Array2<f64>) -> (Array1<f64>, Array2<f64>) {
+    let n = matrix.nrows();
+    let mut a = matrix.clone();
+    let mut vectors = Array2::eye(n);
+
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[[i, j]] * a[[i, j]])
+            .sum();
+        if off_diagonal < 1e-20 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[[p, q]].abs() < 1e-300 {
+                    continue;
+                }
+
+                // the rotation that zeroes a[p][q]
+                let theta = (a[[q, q]] - a[[p, p]]) / (2. * a[[p, q]]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.).sqrt());
+                let c = 1. / (t * t + 1.).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let (akp, akq) = (a[[k, p]], a[[k, q]]);
+                    a[[k, p]] = c * akp - s * akq;
+                    a[[k, q]] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[[p, k]], a[[q, k]]);
+                    a[[p, k]] = c * apk - s * aqk;
+                    a[[q, k]] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (vectors[[k, p]], vectors[[k, q]]);
+                    vectors[[k, p]] = c * vkp - s * vkq;
+                    vectors[[k, q]] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| {
+        a[[j, j]]
+            .partial_cmp(&a[[i, i]])
+            .expect("Tried to compare a NaN")
+    });
+
+    (
+        order.iter().map(|&i| a[[i, i]]).collect(),
+        vectors.select(Axis(1), &order),
+    )
+}
+
+pub struct Pca {
+    pub mean: Array1<f64>,
+    // one column per component, ordered by the variance they explain
+    pub components: Array2<f64>,
+    pub explained_variance_ratio: Array1<f64>,
+}
+
+impl Pca {
+    // `data` has one sample per row.
+    pub fn fit(data: &Array2<f64>) -> Self {
+        let mean = data.mean_axis(Axis(0)).unwrap();
+        let centered = data - &mean;
+        let covariance = centered.t().dot(&centered) / (data.nrows() as f64 - 1.).max(1.);
+
+        let (eigenvalues, components) = symmetric_eigen(&covariance);
+        let explained_variance = eigenvalues.mapv(|v| v.max(0.));
+        let total = explained_variance.sum();
+        let explained_variance_ratio = if total > 0. {
+            &explained_variance / total
+        } else {
+            Array1::zeros(explained_variance.len())
+        };
+
+        Pca {
+            mean,
+            components,
+            explained_variance_ratio,
+        }
+    }
+
+    // Projects the rows of `data` onto the first `n` components.
+    pub fn transform(&self, data: &Array2<f64>, n: usize) -> Array2<f64> {
+        let n = n.min(self.components.ncols());
+        (data - &self.mean).dot(&self.components.slice(s![.., ..n]))
+    }
+}
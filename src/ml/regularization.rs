@@ -0,0 +1,46 @@
+use ndarray::prelude::*;
+
+pub const REGULARIZATIONS: [&str; 4] = ["None", "L1", "L2", "Elastic-Net"];
+
+// Elastic-net penalty on the weights, `l1_ratio` mixes between pure L2 (0)
+// and pure L1 (1). The bias is never passed in here so it stays unpenalized.
+#[derive(Debug, Clone, Copy)]
+pub struct Regularization {
+    pub strength: f64,
+    pub l1_ratio: f64,
+}
+
+impl Regularization {
+    pub fn new(name: &str, strength: f64, l1_ratio: f64) -> Self {
+        let (strength, l1_ratio) = match name {
+            "None" => (0., 0.),
+            "L1" => (strength, 1.),
+            "L2" => (strength, 0.),
+            "Elastic-Net" => (strength, l1_ratio),
+            _ => panic!("unknown regularization {}", name),
+        };
+
+        Regularization { strength, l1_ratio }
+    }
+
+    pub fn cost(&self, weights: &Array2<f64>) -> f64 {
+        let l1 = weights.mapv(f64::abs).sum();
+        let l2 = weights.mapv(|w| w * w).sum() / 2.;
+
+        self.strength * (self.l1_ratio * l1 + (1. - self.l1_ratio) * l2)
+    }
+
+    pub fn gradient(&self, weights: &Array2<f64>) -> Array2<f64> {
+        weights.mapv(|w| {
+            let sign = if w > 0. {
+                1.
+            } else if w < 0. {
+                -1.
+            } else {
+                0.
+            };
+
+            self.strength * (self.l1_ratio * sign + (1. - self.l1_ratio) * w)
+        })
+    }
+}
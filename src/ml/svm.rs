@@ -0,0 +1,150 @@
+use super::descent::GradientDescent;
+use super::model::{Model, Parameter, Settings, Value};
+use super::{
+    class_probabilities, classes, encode, features_and_target, sigmoid, softmax_rows, History,
+};
+use ndarray::prelude::*;
+use ndarray::Zip;
+
+// Mean hinge loss plus `lambda / 2` times the squared norm of the weights,
+// targets are -1 or 1.
+fn hinge_loss(scores: &Array2<f64>, y: &Array2<f64>, weights: &Array2<f64>, lambda: f64) -> f64 {
+    let hinge = (y * scores).mapv(|margin| (1. - margin).max(0.)).sum() / y.ncols() as f64;
+    hinge + lambda * weights.mapv(|w| w * w).sum() / 2.
+}
+
+fn forward_backward(
+    parameters: &[Array2<f64>],
+    x_train: &Array2<f64>,
+    y_train: &Array2<f64>,
+    lambda: f64,
+) -> (f64, Vec<Array2<f64>>) {
+    let (weights, bias) = (&parameters[0], &parameters[1]);
+
+    // forward
+    let scores = weights.t().dot(x_train) + bias;
+    let cost = hinge_loss(&scores, y_train, weights, lambda);
+
+    // backward, only the samples inside the margin contribute
+    let mut inside = y_train.mapv(|y| -y);
+    Zip::from(&mut inside)
+        .and(&scores)
+        .and(y_train)
+        .apply(|gradient, &score, &y| {
+            if y * score >= 1. {
+                *gradient = 0.;
+            }
+        });
+    let d_weights = x_train
+        .dot(&inside.t())
+        .mapv(|z| z / x_train.ncols() as f64)
+        + weights * lambda;
+    let d_bias = inside
+        .sum_axis(Axis(1))
+        .insert_axis(Axis(1))
+        .mapv(|z| z / x_train.ncols() as f64);
+
+    (cost, vec![d_weights, d_bias])
+}
+
+// Trained with sub-gradient descent, targets with more than two classes get
+// one-vs-rest weights for every class. The objective is the usual
+// `|w|² / 2 + C * sum(hinge)` divided by `C` times the number of samples so
+// it stays on the scale of a mean loss.
+pub struct LinearSvm {
+    c: f64,
+    descent: GradientDescent,
+    weights: Array2<f64>,
+    bias: Array2<f64>,
+    classes: Vec<f64>,
+    history: History,
+}
+
+impl LinearSvm {
+    pub fn new(settings: &Settings) -> Self {
+        LinearSvm {
+            c: settings.number("C", 1.),
+            // the hinge loss only has a sub-gradient, it takes more steps than
+            // the smooth losses to settle
+            descent: GradientDescent::new(settings, 0.1, 1000),
+            weights: Array2::zeros([0, 0]),
+            bias: Array2::zeros([0, 0]),
+            classes: Vec::new(),
+            history: History::default(),
+        }
+    }
+
+    fn signed(&self, targets: &Array1<f64>) -> Array2<f64> {
+        encode(targets, &self.classes).mapv(|z| 2. * z - 1.)
+    }
+
+    // Signed distance to the margin for every sample and class.
+    fn decision_function(&self, features: &Array2<f64>) -> Array2<f64> {
+        (self.weights.t().dot(&features.t()) + &self.bias)
+            .t()
+            .to_owned()
+    }
+}
+
+impl Model for LinearSvm {
+    fn fit(&mut self, train_set: &Array2<f64>, validation_set: Option<&Array2<f64>>) {
+        let (features, targets) = features_and_target(train_set);
+
+        self.classes = classes(&targets);
+        let train = (features.t().to_owned(), self.signed(&targets));
+        let validation = validation_set.map(|validation_set| {
+            let (features, targets) = features_and_target(validation_set);
+            (features.t().to_owned(), self.signed(&targets))
+        });
+        let (train, validation) = self.descent.holdout(train, validation);
+
+        let weights = Array2::zeros([train.0.nrows(), train.1.nrows()]);
+        let bias = Array2::zeros([train.1.nrows(), 1]);
+
+        let lambda = 1. / (self.c * train.0.ncols() as f64);
+        let (history, mut parameters) = self.descent.train(
+            vec![weights, bias],
+            train,
+            validation,
+            &|parameters, x, y| forward_backward(parameters, x, y, lambda),
+            &|parameters, x, y| {
+                let scores = parameters[0].t().dot(x) + &parameters[1];
+                hinge_loss(&scores, y, &parameters[0], lambda)
+            },
+        );
+
+        self.history = history;
+        self.bias = parameters.pop().unwrap();
+        self.weights = parameters.pop().unwrap();
+    }
+
+    // The margins aren't probabilities, they are squashed with a sigmoid (or
+    // a softmax across the classes) so they rank the same way.
+    fn predict_proba(&self, features: &Array2<f64>) -> Option<Array2<f64>> {
+        let scores = self.decision_function(features);
+        let probabilities = if scores.ncols() == 1 {
+            scores.mapv(sigmoid)
+        } else {
+            softmax_rows(scores)
+        };
+
+        Some(class_probabilities(self.classes.len(), probabilities))
+    }
+
+    fn classes(&self) -> &[f64] {
+        &self.classes
+    }
+
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
+
+    // the margin term already regularizes the weights
+    fn parameters(&self) -> Vec<Parameter> {
+        let mut parameters = vec![Parameter::new("C", Value::Number(self.c))];
+        parameters.extend(self.descent.parameters().into_iter().filter(|parameter| {
+            !["Regularization", "Strength", "L1 Ratio"].contains(&parameter.name)
+        }));
+        parameters
+    }
+}
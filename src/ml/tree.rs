@@ -0,0 +1,288 @@
+use super::model::{Model, Parameter, Settings, Value};
+use super::{classes, features_and_target, random_shuffle, seeded_rng};
+use ndarray::prelude::*;
+use rand::rngs::StdRng;
+
+pub const CRITERIA: [&str; 2] = ["Gini", "Entropy"];
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    // training samples of every class that ended up in this node
+    pub counts: Vec<usize>,
+    pub split: Option<Split>,
+}
+
+// Samples with `feature <= threshold` go to the left child.
+#[derive(Debug, Clone)]
+pub struct Split {
+    pub feature: usize,
+    pub threshold: f64,
+    pub left: Box<Node>,
+    pub right: Box<Node>,
+}
+
+impl Node {
+    pub fn leaf(&self, sample: ArrayView1<f64>) -> &Node {
+        match &self.split {
+            Some(split) if sample[split.feature] <= split.threshold => split.left.leaf(sample),
+            Some(split) => split.right.leaf(sample),
+            None => self,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        match &self.split {
+            Some(split) => 1 + split.left.depth().max(split.right.depth()),
+            None => 0,
+        }
+    }
+
+    pub fn leaves(&self) -> usize {
+        match &self.split {
+            Some(split) => split.left.leaves() + split.right.leaves(),
+            None => 1,
+        }
+    }
+
+    pub fn probabilities(&self) -> Array1<f64> {
+        let total = self.counts.iter().sum::<usize>() as f64;
+        self.counts
+            .iter()
+            .map(|&count| count as f64 / total)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeOptions {
+    pub max_depth: usize,
+    pub min_samples_leaf: usize,
+    pub criterion: String,
+    // how many randomly picked features are tried at every split, `None`
+    // tries all of them
+    pub max_features: Option<usize>,
+}
+
+fn impurity(criterion: &str, counts: &[usize]) -> f64 {
+    let total = counts.iter().sum::<usize>() as f64;
+    let proportions = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| count as f64 / total);
+
+    match criterion {
+        "Gini" => 1. - proportions.map(|p| p * p).sum::<f64>(),
+        "Entropy" => -proportions.map(|p| p * p.log2()).sum::<f64>(),
+        _ => panic!("unknown criterion {}", criterion),
+    }
+}
+
+fn count(targets: &[usize], rows: &[usize], n_classes: usize) -> Vec<usize> {
+    let mut counts = vec![0; n_classes];
+    for &row in rows {
+        counts[targets[row]] += 1;
+    }
+    counts
+}
+
+// Returns the (weighted impurity, feature, threshold) of the best split of
+// `rows` that leaves at least `min_samples_leaf` samples on both sides.
+fn best_split(
+    features: &Array2<f64>,
+    targets: &[usize],
+    rows: &[usize],
+    candidates: &[usize],
+    options: &TreeOptions,
+    n_classes: usize,
+) -> Option<(f64, usize, f64)> {
+    let min_leaf = options.min_samples_leaf.max(1);
+    let mut best: Option<(f64, usize, f64)> = None;
+
+    for &feature in candidates {
+        let mut sorted = rows.to_vec();
+        sorted.sort_by(|&a, &b| {
+            features[[a, feature]]
+                .partial_cmp(&features[[b, feature]])
+                .expect("Tried to compare a NaN")
+        });
+
+        let mut left = vec![0; n_classes];
+        let mut right = count(targets, &sorted, n_classes);
+        for idx in 0..sorted.len() - 1 {
+            left[targets[sorted[idx]]] += 1;
+            right[targets[sorted[idx]]] -= 1;
+
+            let (value, next) = (
+                features[[sorted[idx], feature]],
+                features[[sorted[idx + 1], feature]],
+            );
+            let left_size = idx + 1;
+            let right_size = sorted.len() - left_size;
+            if value == next || left_size < min_leaf || right_size < min_leaf {
+                continue;
+            }
+
+            let score = (left_size as f64 * impurity(&options.criterion, &left)
+                + right_size as f64 * impurity(&options.criterion, &right))
+                / sorted.len() as f64;
+            let better = match best {
+                Some((lowest, _, _)) => score < lowest,
+                None => true,
+            };
+            if better {
+                best = Some((score, feature, (value + next) / 2.));
+            }
+        }
+    }
+
+    best
+}
+
+// Grows a classification tree, `targets` holds the index of every row's class.
+pub fn grow(
+    features: &Array2<f64>,
+    targets: &[usize],
+    rows: Vec<usize>,
+    n_classes: usize,
+    options: &TreeOptions,
+    rng: &mut StdRng,
+) -> Node {
+    grow_node(features, targets, rows, n_classes, options, rng, 0)
+}
+
+fn grow_node(
+    features: &Array2<f64>,
+    targets: &[usize],
+    rows: Vec<usize>,
+    n_classes: usize,
+    options: &TreeOptions,
+    rng: &mut StdRng,
+    depth: usize,
+) -> Node {
+    let counts = count(targets, &rows, n_classes);
+    let pure = counts.iter().filter(|&&count| count > 0).count() <= 1;
+    if pure || depth >= options.max_depth {
+        return Node {
+            counts,
+            split: None,
+        };
+    }
+
+    let mut candidates: Vec<usize> = (0..features.ncols()).collect();
+    if let Some(max_features) = options.max_features {
+        random_shuffle(&mut candidates, rng);
+        candidates.truncate(max_features.max(1));
+    }
+
+    let split = best_split(features, targets, &rows, &candidates, options, n_classes).map(
+        |(_, feature, threshold)| {
+            let (left, right): (Vec<usize>, Vec<usize>) = rows
+                .iter()
+                .partition(|&&row| features[[row, feature]] <= threshold);
+
+            Split {
+                feature,
+                threshold,
+                left: Box::new(grow_node(
+                    features,
+                    targets,
+                    left,
+                    n_classes,
+                    options,
+                    rng,
+                    depth + 1,
+                )),
+                right: Box::new(grow_node(
+                    features,
+                    targets,
+                    right,
+                    n_classes,
+                    options,
+                    rng,
+                    depth + 1,
+                )),
+            }
+        },
+    );
+
+    Node { counts, split }
+}
+
+// Index of every target within `classes`.
+pub fn class_indices(targets: &Array1<f64>, classes: &[f64]) -> Vec<usize> {
+    targets
+        .iter()
+        .map(|target| classes.iter().position(|class| class == target).unwrap())
+        .collect()
+}
+
+pub struct DecisionTree {
+    options: TreeOptions,
+    seed: Option<u64>,
+    root: Option<Node>,
+    classes: Vec<f64>,
+}
+
+impl DecisionTree {
+    pub fn new(settings: &Settings) -> Self {
+        DecisionTree {
+            options: TreeOptions {
+                max_depth: settings.integer("Max Depth", 5),
+                min_samples_leaf: settings.integer("Min Samples Leaf", 1),
+                criterion: settings.choice("Criterion", "Gini"),
+                max_features: None,
+            },
+            seed: settings.seed,
+            root: None,
+            classes: Vec::new(),
+        }
+    }
+}
+
+impl Model for DecisionTree {
+    fn fit(&mut self, train_set: &Array2<f64>, _validation_set: Option<&Array2<f64>>) {
+        let (features, targets) = features_and_target(train_set);
+
+        self.classes = classes(&targets);
+        let targets = class_indices(&targets, &self.classes);
+        self.root = Some(grow(
+            &features,
+            &targets,
+            (0..features.nrows()).collect(),
+            self.classes.len(),
+            &self.options,
+            &mut seeded_rng(self.seed),
+        ));
+    }
+
+    fn predict_proba(&self, features: &Array2<f64>) -> Option<Array2<f64>> {
+        let root = self.root.as_ref().unwrap();
+        let mut probabilities = Array2::zeros([features.nrows(), self.classes.len()]);
+        for (mut row, sample) in probabilities.outer_iter_mut().zip(features.outer_iter()) {
+            row.assign(&root.leaf(sample).probabilities());
+        }
+        Some(probabilities)
+    }
+
+    fn classes(&self) -> &[f64] {
+        &self.classes
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![
+            Parameter::new("Max Depth", Value::Integer(self.options.max_depth)),
+            Parameter::new(
+                "Min Samples Leaf",
+                Value::Integer(self.options.min_samples_leaf),
+            ),
+            Parameter::new(
+                "Criterion",
+                Value::Choice(&CRITERIA, self.options.criterion.clone()),
+            ),
+        ]
+    }
+
+    fn tree(&self) -> Option<&Node> {
+        self.root.as_ref()
+    }
+}
@@ -0,0 +1,191 @@
+use super::form::Form;
+use crate::ml::kmeans::{self, KMeansOptions};
+use crate::ml::model::{Parameter, Value};
+use crate::utils;
+use gtk::prelude::*;
+use plotters::prelude::*;
+use polars::prelude::*;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub fn render_page(window: &gtk::ApplicationWindow, df_cell: Rc<RefCell<Option<DataFrame>>>) {
+    let vbox = gtk::BoxBuilder::new()
+        .orientation(gtk::Orientation::Vertical)
+        .margin(10)
+        .spacing(10)
+        .build();
+
+    // Params Inputs
+
+    let params_box = gtk::GridBuilder::new()
+        .row_spacing(10)
+        .column_spacing(10)
+        .hexpand(true)
+        .build();
+    vbox.pack_start(&params_box, false, false, 0);
+
+    let form = Form::render(
+        &params_box,
+        vec![
+            Parameter::new("K", Value::Integer(3)),
+            Parameter::new("Max Iterations", Value::Integer(100)),
+            Parameter::new("Restarts", Value::Integer(10)),
+            Parameter::new("Max K", Value::Integer(10)),
+            Parameter::new("Seed", Value::Integer(rand::random::<u32>() as usize)),
+        ],
+    );
+    let read_options = move || {
+        let settings = form.read(None);
+        (
+            KMeansOptions {
+                k: settings.integer("K", 3),
+                max_iterations: settings.integer("Max Iterations", 100),
+                restarts: settings.integer("Restarts", 10),
+                seed: Some(settings.integer("Seed", 0) as u64),
+            },
+            settings,
+        )
+    };
+    let read_options = Rc::new(read_options);
+
+    let buttons_box = gtk::BoxBuilder::new()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(10)
+        .build();
+    vbox.pack_start(&buttons_box, false, false, 0);
+
+    let cluster_button = gtk::ButtonBuilder::new().label("Cluster").build();
+    buttons_box.pack_start(&cluster_button, true, true, 0);
+    let elbow_button = gtk::ButtonBuilder::new().label("Elbow Plot").build();
+    buttons_box.pack_start(&elbow_button, true, true, 0);
+
+    let graph_box = gtk::BoxBuilder::new().build();
+    vbox.pack_start(&graph_box, true, true, 0);
+
+    let sizes_label = gtk::LabelBuilder::new()
+        .halign(gtk::Align::Start)
+        .selectable(true)
+        .build();
+    vbox.pack_start(&sizes_label, false, false, 0);
+
+    let scroll_window = gtk::ScrolledWindowBuilder::new()
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .hscrollbar_policy(gtk::PolicyType::Automatic)
+        .build();
+    vbox.pack_start(&scroll_window, true, true, 0);
+
+    let tree_view = utils::create_tree_view(df_cell.borrow().as_ref().unwrap());
+    tree_view.show();
+    scroll_window.add(&tree_view);
+
+    // Cluster Button
+
+    let df_cell_cloned = Rc::clone(&df_cell);
+    let read_options_cloned = Rc::clone(&read_options);
+    cluster_button.connect_clicked(move |_| {
+        let (options, _) = read_options_cloned();
+        let df = df_cell_cloned.borrow();
+        let df = df.as_ref().unwrap();
+
+        let data = df.to_ndarray::<Float64Type>().unwrap();
+        let clustering = kmeans::kmeans(&data, &options);
+
+        let mut summary: Vec<String> = clustering
+            .sizes()
+            .iter()
+            .enumerate()
+            .map(|(label, size)| format!("Cluster {}: {}", label, size))
+            .collect();
+        summary.push(format!("Inertia {:.3}", clustering.inertia));
+        sizes_label.set_text(&summary.join("    "));
+
+        let labels = Series::new(
+            "Cluster",
+            clustering
+                .labels
+                .iter()
+                .map(|&label| label as f64)
+                .collect::<Vec<f64>>(),
+        );
+
+        utils::kill_children(&scroll_window);
+        let tree_view = utils::create_tree_view(&df.hstack(&[labels]).unwrap());
+        tree_view.show();
+        scroll_window.add(&tree_view);
+    });
+
+    // Elbow Button
+
+    elbow_button.connect_clicked(move |_| {
+        let (options, settings) = read_options();
+        let data = df_cell
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .to_ndarray::<Float64Type>()
+            .unwrap();
+        let inertias = kmeans::elbow(&data, settings.integer("Max K", 10), &options);
+
+        utils::kill_children(&graph_box);
+        draw_elbow_graph(&graph_box, inertias);
+    });
+
+    // Window
+
+    window.add(&utils::wrap_in_header(
+        "Cluster",
+        "Group the rows with k-means, without a target",
+        &vbox,
+    ));
+    window.show_all();
+}
+
+fn draw_elbow_graph(container: &gtk::Box, inertias: Vec<(usize, f64)>) {
+    let high = inertias
+        .iter()
+        .map(|(_, inertia)| *inertia)
+        .fold(0., f64::max)
+        * 1.1;
+    let max_k = inertias.len();
+
+    let drawing_area = gtk::DrawingArea::new();
+    container.pack_start(&drawing_area, true, true, 0);
+
+    drawing_area.connect_draw(move |da, cr| {
+        let root_area = plotters_cairo::CairoBackend::new(
+            cr,
+            (
+                da.get_allocated_width() as u32,
+                da.get_allocated_height() as u32,
+            ),
+        )
+        .unwrap()
+        .into_drawing_area();
+
+        root_area.fill(&WHITE).unwrap();
+
+        let mut ctx = ChartBuilder::on(&root_area)
+            .margin(20)
+            .set_label_area_size(LabelAreaPosition::Left, 40)
+            .set_label_area_size(LabelAreaPosition::Bottom, 40)
+            .caption("Inertia vs K", ("sans-serif", 15))
+            .build_cartesian_2d(0..max_k + 1, 0f64..high)
+            .unwrap();
+
+        ctx.configure_mesh().draw().unwrap();
+
+        ctx.draw_series(LineSeries::new(inertias.clone(), &RED))
+            .unwrap();
+        ctx.draw_series(
+            inertias
+                .iter()
+                .map(|&point| Circle::new(point, 4, RED.filled())),
+        )
+        .unwrap();
+
+        gtk::Inhibit(false)
+    });
+
+    container.show_all();
+}
@@ -0,0 +1,156 @@
+use crate::ml::model::{Parameter, Settings, Value};
+use crate::utils;
+use gtk::prelude::*;
+
+const COLUMNS: usize = 4;
+
+#[derive(Clone)]
+enum Input {
+    Text(gtk::TextView),
+    Choice(gtk::ComboBoxText),
+    Flag(gtk::CheckButton),
+}
+
+impl Input {
+    fn widget(&self) -> gtk::Widget {
+        match self {
+            Input::Text(text) => text.clone().upcast(),
+            Input::Choice(combo) => combo.clone().upcast(),
+            Input::Flag(check) => check.clone().upcast(),
+        }
+    }
+
+    // The value as matched against `Parameter::enabled_when`.
+    fn state(&self) -> String {
+        match self {
+            Input::Choice(combo) => combo.get_active_text().unwrap().to_string(),
+            Input::Flag(check) => check.get_active().to_string(),
+            Input::Text(text) => utils::get_text(text.get_buffer().unwrap()),
+        }
+    }
+}
+
+// A grid of inputs generated from a model's parameter descriptions.
+#[derive(Clone)]
+pub struct Form {
+    inputs: Vec<(Parameter, Input)>,
+}
+
+impl Form {
+    pub fn render(grid: &gtk::Grid, parameters: Vec<Parameter>) -> Form {
+        utils::kill_children(grid);
+
+        let inputs: Vec<(Parameter, Input)> = parameters
+            .into_iter()
+            .enumerate()
+            .map(|(idx, parameter)| {
+                let (x, y) = ((idx % COLUMNS) as i32, (idx / COLUMNS) as i32);
+                grid.attach(
+                    &gtk::LabelBuilder::new().label(parameter.name).build(),
+                    x * 2,
+                    y,
+                    1,
+                    1,
+                );
+
+                let input = match &parameter.value {
+                    Value::Number(x) => Input::Text(text_input(&x.to_string())),
+                    Value::Integer(x) => Input::Text(text_input(&x.to_string())),
+                    Value::Text(x) => Input::Text(text_input(x)),
+                    Value::Choice(options, selected) => {
+                        let combo = gtk::ComboBoxText::new();
+                        for option in options.iter() {
+                            combo.append_text(option);
+                        }
+                        let active = options.iter().position(|option| option == selected);
+                        combo.set_active(active.map(|idx| idx as u32));
+                        Input::Choice(combo)
+                    }
+                    Value::Flag(x) => {
+                        Input::Flag(gtk::CheckButtonBuilder::new().active(*x).build())
+                    }
+                };
+                grid.attach(&input.widget(), x * 2 + 1, y, 1, 1);
+
+                (parameter, input)
+            })
+            .collect();
+
+        for (parameter, input) in inputs.iter() {
+            let (name, values) = match parameter.enabled_when {
+                Some(enabled_when) => enabled_when,
+                None => continue,
+            };
+            let controller = match inputs.iter().find(|(other, _)| other.name == name) {
+                Some((_, controller)) => controller.clone(),
+                None => continue,
+            };
+
+            let dependent = input.widget();
+            let update = move |controller: &Input| {
+                dependent.set_sensitive(values.contains(&controller.state().as_str()));
+            };
+            update(&controller);
+
+            let controller_clone = controller.clone();
+            match &controller {
+                Input::Choice(combo) => {
+                    combo.connect_changed(move |_| update(&controller_clone));
+                }
+                Input::Flag(check) => {
+                    check.connect_toggled(move |_| update(&controller_clone));
+                }
+                Input::Text(_) => {}
+            }
+        }
+
+        grid.show_all();
+
+        Form { inputs }
+    }
+
+    // The first problem with the text of a parameter, labelled with the
+    // parameter's name. Disabled inputs are read as well, so they are checked
+    // too.
+    pub fn check(&self) -> Result<(), String> {
+        for (parameter, input) in self.inputs.iter() {
+            if let (Some(check), Input::Text(_)) = (parameter.check, input) {
+                check(&input.state())
+                    .map_err(|message| format!("{}: {}", parameter.name, message))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read(&self, seed: Option<u64>) -> Settings {
+        let values = self
+            .inputs
+            .iter()
+            .map(|(parameter, input)| {
+                let value = match (&parameter.value, input) {
+                    (Value::Number(_), Input::Text(text)) => Value::Number(utils::parse_text(text)),
+                    (Value::Integer(_), Input::Text(text)) => {
+                        Value::Integer(utils::parse_text(text))
+                    }
+                    (Value::Choice(options, _), Input::Choice(combo)) => {
+                        Value::Choice(options, combo.get_active_text().unwrap().to_string())
+                    }
+                    (Value::Flag(_), Input::Flag(check)) => Value::Flag(check.get_active()),
+                    (_, input) => Value::Text(input.state()),
+                };
+
+                (parameter.name, value)
+            })
+            .collect();
+
+        Settings { values, seed }
+    }
+}
+
+fn text_input(text: &str) -> gtk::TextView {
+    gtk::TextViewBuilder::new()
+        .buffer(&gtk::TextBufferBuilder::new().text(text).build())
+        .hexpand(true)
+        .border_width(5)
+        .build()
+}
@@ -1,4 +1,6 @@
 pub mod choose;
+pub mod cluster;
+mod form;
 pub mod model;
 pub mod processing;
 
@@ -15,6 +17,7 @@ pub enum Pages {
     Choose,
     Processing,
     Model,
+    Cluster,
 }
 
 pub fn paint(window: &gtk::ApplicationWindow) {
@@ -31,6 +34,9 @@ pub fn paint(window: &gtk::ApplicationWindow) {
             Pages::Model => {
                 model::render_page(&window, Rc::clone(d));
             }
+            Pages::Cluster => {
+                cluster::render_page(&window, Rc::clone(d));
+            }
         })
     });
 }
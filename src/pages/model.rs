@@ -1,17 +1,21 @@
+use super::form::Form;
 use crate::ml;
+use crate::ml::confusion::{self, ConfusionMatrix};
+use crate::ml::curves::{self, CalibrationBin, PrPoint, RocPoint};
+use crate::ml::model::{Model, Settings};
+use crate::ml::tree::Node;
 use crate::utils;
 use gtk::prelude::*;
 use ndarray::prelude::*;
 use plotters::prelude::*;
-use polars::prelude::DataFrame;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use polars::prelude::{DataFrame, NamedFrom, Series};
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 pub fn render_page(window: &gtk::ApplicationWindow, df_cell: Rc<RefCell<Option<DataFrame>>>) {
-    let (train_set, test_set) = ml::split(df_cell.borrow().as_ref().unwrap(), 0.7);
-    let weights: Rc<RefCell<Option<Array2<f64>>>> = Rc::new(RefCell::new(None));
-    let bias: Rc<RefCell<Option<f64>>> = Rc::new(RefCell::new(None));
+    let model: Rc<RefCell<Option<Box<dyn Model>>>> = Rc::new(RefCell::new(None));
 
     let vbox = gtk::BoxBuilder::new()
         .orientation(gtk::Orientation::Vertical)
@@ -19,6 +23,62 @@ pub fn render_page(window: &gtk::ApplicationWindow, df_cell: Rc<RefCell<Option<D
         .spacing(10)
         .build();
 
+    // Split
+
+    let split_grid = gtk::GridBuilder::new()
+        .row_spacing(10)
+        .column_spacing(10)
+        .hexpand(true)
+        .build();
+    vbox.pack_start(&split_grid, false, false, 0);
+
+    let split_form = SplitForm {
+        seed_text: add_param(
+            &split_grid,
+            "Seed",
+            &rand::random::<u32>().to_string(),
+            0,
+            0,
+        ),
+        train_ratio_text: add_param(&split_grid, "Train Ratio", "0.7", 1, 0),
+        validation_ratio_text: add_param(&split_grid, "Validation Ratio", "0", 2, 0),
+        shuffle_check: gtk::CheckButtonBuilder::new()
+            .label("Shuffle")
+            .active(true)
+            .build(),
+        stratified_check: gtk::CheckButtonBuilder::new().label("Stratified").build(),
+    };
+    split_grid.attach(&split_form.shuffle_check, 6, 0, 1, 1);
+    split_grid.attach(&split_form.stratified_check, 7, 0, 1, 1);
+
+    // only a categorical target can be stratified
+    let categorical = ml::target_is_categorical(df_cell.borrow().as_ref().unwrap());
+    split_form.stratified_check.set_sensitive(categorical);
+
+    let split_button = gtk::ButtonBuilder::new().label("Split").build();
+    split_grid.attach(&split_button, 8, 0, 1, 1);
+
+    let sizes_label = gtk::LabelBuilder::new().halign(gtk::Align::Start).build();
+    split_grid.attach(&sizes_label, 0, 1, 9, 1);
+
+    let split_options = Rc::new(Cell::new(split_form.read()));
+    let split_cell = Rc::new(RefCell::new(ml::split(
+        df_cell.borrow().as_ref().unwrap(),
+        &split_options.get(),
+    )));
+    show_split_sizes(&sizes_label, &split_cell.borrow());
+
+    let seed_text = split_form.seed_text.clone();
+
+    // Algorithm
+
+    let algorithms = ml::algorithms();
+    let algorithm_combo = gtk::ComboBoxText::new();
+    for algorithm in algorithms.iter() {
+        algorithm_combo.append_text(algorithm.name);
+    }
+    vbox.pack_start(&algorithm_combo, false, false, 0);
+
     // Params Inputs
 
     let params_box = gtk::GridBuilder::new()
@@ -28,61 +88,212 @@ pub fn render_page(window: &gtk::ApplicationWindow, df_cell: Rc<RefCell<Option<D
         .build();
     vbox.pack_start(&params_box, false, false, 0);
 
-    params_box.attach(
-        &gtk::LabelBuilder::new().label("Learning Rate").build(),
-        0,
-        0,
-        1,
-        1,
-    );
-    let lr_text = gtk::TextViewBuilder::new()
-        .buffer(&gtk::TextBufferBuilder::new().text("1").build())
+    let form: Rc<RefCell<Option<Form>>> = Rc::new(RefCell::new(None));
+
+    // what is wrong with the parameters, when the model can't be created
+    let form_label = gtk::LabelBuilder::new().halign(gtk::Align::Start).build();
+    vbox.pack_start(&form_label, false, false, 0);
+
+    // filled in on testing, the metrics depend on the task of the model
+    let metrics_box = gtk::GridBuilder::new()
+        .row_spacing(10)
+        .column_spacing(10)
         .hexpand(true)
-        .border_width(5)
         .build();
-    params_box.attach(&lr_text, 1, 0, 1, 1);
+    let confusion_grid = gtk::GridBuilder::new()
+        .row_spacing(10)
+        .column_spacing(20)
+        .build();
 
-    params_box.attach(
-        &gtk::LabelBuilder::new().label("Iterations").build(),
-        2,
-        0,
-        1,
-        1,
-    );
-    let iterations_text = gtk::TextViewBuilder::new()
-        .buffer(&gtk::TextBufferBuilder::new().text("100").build())
-        .hexpand(true)
-        .border_width(5)
+    let threshold_scale = gtk::Scale::with_range(gtk::Orientation::Horizontal, 0., 1., 0.01);
+    // thresholds picked from the curves aren't rounded to the step
+    threshold_scale.set_round_digits(-1);
+    threshold_scale.set_value(0.5);
+    let best_f1_button = gtk::ButtonBuilder::new().label("Best F1").build();
+    let test_button = gtk::ButtonBuilder::new().label("Test").build();
+    let average_combo = gtk::ComboBoxText::new();
+    for average in confusion::AVERAGES.iter() {
+        average_combo.append_text(average);
+    }
+    average_combo.set_active(Some(0));
+
+    let evaluation_view = EvaluationView {
+        metrics_box: metrics_box.clone(),
+        confusion_grid: confusion_grid.clone(),
+        threshold_scale: threshold_scale.clone(),
+        best_f1_button: best_f1_button.clone(),
+        average_combo: average_combo.clone(),
+        test_button: test_button.clone(),
+    };
+    evaluation_view.clear();
+
+    // the last test prediction, dropped with the model it came from
+    let prediction: Rc<RefCell<Option<ml::Prediction>>> = Rc::new(RefCell::new(None));
+
+    let graph_box = gtk::BoxBuilder::new().build();
+    // the curves of the test set sit next to the cost graph
+    let curves_box = gtk::BoxBuilder::new().homogeneous(true).build();
+    let tree_window = gtk::ScrolledWindowBuilder::new()
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .hscrollbar_policy(gtk::PolicyType::Automatic)
         .build();
-    params_box.attach(&iterations_text, 3, 0, 1, 1);
+
+    // everything that came out of the trained model, dropped as soon as the
+    // data it was trained on changes
+    let reset_results = {
+        let model = Rc::clone(&model);
+        let prediction = Rc::clone(&prediction);
+        let evaluation_view = evaluation_view.clone();
+        let graph_box = graph_box.clone();
+        let curves_box = curves_box.clone();
+        let tree_window = tree_window.clone();
+        move || {
+            model.replace(None);
+            prediction.replace(None);
+            evaluation_view.clear();
+            utils::kill_children(&graph_box);
+            utils::kill_children(&curves_box);
+            utils::kill_children(&tree_window);
+        }
+    };
+    let reset_results = Rc::new(reset_results);
+
+    // a new split invalidates the model, its test set could hold rows it was
+    // trained on
+    let df_cell_cloned = Rc::clone(&df_cell);
+    let split_cell_cloned = Rc::clone(&split_cell);
+    let split_options_cloned = Rc::clone(&split_options);
+    let reset_results_cloned = Rc::clone(&reset_results);
+    split_button.connect_clicked(move |_| {
+        let options = split_form.read();
+        if options.train_ratio <= 0.
+            || options.validation_ratio < 0.
+            || options.train_ratio + options.validation_ratio >= 1.
+        {
+            sizes_label.set_text(
+                "The train ratio must be above 0 and leave part of the rows for the test set",
+            );
+            return;
+        }
+
+        // too few rows can still round a partition down to nothing
+        let (train_set, validation_set, test_set) =
+            ml::split(df_cell_cloned.borrow().as_ref().unwrap(), &options);
+        if train_set.nrows() == 0 || test_set.nrows() == 0 {
+            sizes_label.set_text("Too few rows for these ratios, the train or test set is empty");
+            return;
+        }
+
+        split_options_cloned.set(options);
+        split_cell_cloned.replace((train_set, validation_set, test_set));
+        show_split_sizes(&sizes_label, &split_cell_cloned.borrow());
+        reset_results_cloned();
+    });
+
+    let form_cloned = Rc::clone(&form);
+    let algorithms_cloned = algorithms.clone();
+    let reset_results_cloned = Rc::clone(&reset_results);
+    algorithm_combo.connect_changed(move |combo| {
+        let algorithm = &algorithms_cloned[combo.get_active().unwrap() as usize];
+        let parameters = (algorithm.create)(&Settings::default()).parameters();
+        form_cloned.replace(Some(Form::render(&params_box, parameters)));
+        reset_results_cloned();
+    });
+    algorithm_combo.set_active(Some(0));
+
+    let algorithm_combo_cloned = algorithm_combo.clone();
+    let form_cloned = Rc::clone(&form);
+    // trained models are seeded like the split they were trained on, so the
+    // seed shown with the results reproduces both
+    let create_model = move |seed: u64| {
+        let algorithm = &algorithms[algorithm_combo_cloned.get_active().unwrap() as usize];
+        let form = form_cloned.borrow();
+        let form = form.as_ref().unwrap();
+        if let Err(message) = form.check() {
+            form_label.set_text(&message);
+            return Err(message);
+        }
+        form_label.set_text("");
+
+        Ok((algorithm.create)(&form.read(Some(seed))))
+    };
+    let create_model = Rc::new(create_model);
 
     // Train Button
 
-    let graph_box = gtk::BoxBuilder::new().build();
     let graph_box_clone = graph_box.clone();
+    let curves_box_clone = curves_box.clone();
+    let tree_window_clone = tree_window.clone();
+    let df_cell_cloned = Rc::clone(&df_cell);
     let train_button = gtk::ButtonBuilder::new().label("Train").build();
-    let weights_cloned = Rc::clone(&weights);
-    let bias_cloned = Rc::clone(&bias);
+    let model_cloned = Rc::clone(&model);
+    let create_model_cloned = Rc::clone(&create_model);
+    let split_cell_cloned = Rc::clone(&split_cell);
+    let split_options_cloned = Rc::clone(&split_options);
+    let evaluation_view_cloned = evaluation_view.clone();
+    let prediction_cloned = Rc::clone(&prediction);
     train_button.connect_clicked(move |_| {
-        let lr = utils::get_text(lr_text.get_buffer().unwrap())
-            .parse::<f64>()
-            .unwrap();
-        let iterations = utils::get_text(iterations_text.get_buffer().unwrap())
-            .parse::<usize>()
-            .unwrap();
+        // the form label already says what is wrong
+        let seed = split_options_cloned.get().seed.unwrap();
+        let mut trained_model = match create_model_cloned(seed) {
+            Ok(trained_model) => trained_model,
+            Err(_) => return,
+        };
+        let test_costs = {
+            let (train_set, validation_set, test_set) = &*split_cell_cloned.borrow();
+            trained_model.fit(train_set, validation_set.as_ref());
+            trained_model.staged_costs(test_set).unwrap_or_default()
+        };
 
-        let (costs, trained_weights, trained_bias) = ml::train(&train_set, lr, iterations);
-        RefCell::replace(&weights_cloned, Some(trained_weights));
-        RefCell::replace(&bias_cloned, Some(trained_bias));
+        utils::kill_children(&graph_box_clone);
+        utils::kill_children(&curves_box_clone);
+        prediction_cloned.replace(None);
+        evaluation_view_cloned.clear();
+        if let Some(history) = trained_model.history() {
+            let test_costs = test_costs
+                .into_iter()
+                .enumerate()
+                .map(|(round, cost)| (round + 1, cost))
+                .collect();
+            draw_costs_graph(&graph_box_clone, history.clone(), test_costs);
+        }
 
-        draw_costs_graph(&graph_box_clone, costs, iterations);
+        utils::kill_children(&tree_window_clone);
+        if let Some(root) = trained_model.tree() {
+            let mut feature_names: Vec<String> = df_cell_cloned
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .get_column_names()
+                .into_iter()
+                .map(String::from)
+                .collect();
+            feature_names.pop();
+
+            draw_tree(
+                &tree_window_clone,
+                root.clone(),
+                feature_names,
+                trained_model.classes().to_vec(),
+            );
+        }
+
+        model_cloned.replace(Some(trained_model));
+        evaluation_view_cloned.test_button.set_sensitive(true);
     });
     vbox.pack_start(&train_button, false, false, 0);
-    vbox.pack_start(&graph_box, true, true, 0);
+    let charts_box = gtk::BoxBuilder::new()
+        .orientation(gtk::Orientation::Horizontal)
+        .homogeneous(true)
+        .spacing(10)
+        .build();
+    charts_box.pack_start(&graph_box, true, true, 0);
+    charts_box.pack_start(&curves_box, true, true, 0);
+    vbox.pack_start(&charts_box, true, true, 0);
+    vbox.pack_start(&tree_window, true, true, 0);
 
     // Test Button
 
-    let test_button = gtk::ButtonBuilder::new().label("Test").build();
     vbox.pack_start(&test_button, false, false, 0);
 
     let diff_window = gtk::ScrolledWindowBuilder::new()
@@ -93,63 +304,237 @@ pub fn render_page(window: &gtk::ApplicationWindow, df_cell: Rc<RefCell<Option<D
 
     // Metrics
 
-    let metrics_box = gtk::GridBuilder::new()
-        .row_spacing(10)
-        .column_spacing(10)
-        .hexpand(true)
+    let evaluation_box = gtk::BoxBuilder::new()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(20)
         .build();
-    vbox.pack_start(&metrics_box, false, false, 0);
+    evaluation_box.pack_start(&metrics_box, true, true, 0);
+    evaluation_box.pack_start(&confusion_grid, false, false, 0);
+    vbox.pack_start(&evaluation_box, false, false, 0);
 
-    let accuracy_buf = add_label_and_text(&metrics_box, "Accuracy", 0, 0);
-    let precision_buf = add_label_and_text(&metrics_box, "Precision", 1, 0);
-    let recall_buf = add_label_and_text(&metrics_box, "Recall", 0, 1);
-    let f1_score_buf = add_label_and_text(&metrics_box, "F1 Score", 1, 1);
+    let controls_box = gtk::BoxBuilder::new()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(10)
+        .build();
+    vbox.pack_start(&controls_box, false, false, 0);
+
+    // only binary classifiers have a decision threshold to move
+    controls_box.pack_start(
+        &gtk::LabelBuilder::new().label("Threshold").build(),
+        false,
+        false,
+        0,
+    );
+    controls_box.pack_start(&threshold_scale, true, true, 0);
+    controls_box.pack_start(&best_f1_button, false, false, 0);
+
+    // and only multi-class ones need their metrics averaged
+    controls_box.pack_start(
+        &gtk::LabelBuilder::new().label("Average").build(),
+        false,
+        false,
+        0,
+    );
+    controls_box.pack_start(&average_combo, false, false, 0);
+
+    let prediction_cloned = Rc::clone(&prediction);
+    let evaluation_view_cloned = evaluation_view.clone();
+    let split_options_cloned = Rc::clone(&split_options);
+    let curves_box_clone = curves_box.clone();
+    threshold_scale.connect_value_changed(move |_| {
+        if let Some(prediction) = prediction_cloned.borrow().as_ref() {
+            curves_box_clone.queue_draw();
+            evaluation_view_cloned.show(prediction, split_options_cloned.get().seed.unwrap());
+        }
+    });
+
+    let prediction_cloned = Rc::clone(&prediction);
+    let evaluation_view_cloned = evaluation_view.clone();
+    let split_options_cloned = Rc::clone(&split_options);
+    average_combo.connect_changed(move |_| {
+        if let Some(prediction) = prediction_cloned.borrow().as_ref() {
+            evaluation_view_cloned.show(prediction, split_options_cloned.get().seed.unwrap());
+        }
+    });
+
+    let prediction_cloned = Rc::clone(&prediction);
+    let threshold_scale_cloned = threshold_scale.clone();
+    best_f1_button.connect_clicked(move |_| {
+        let best = prediction_cloned
+            .borrow()
+            .as_ref()
+            .and_then(|prediction| prediction.precision_recall_curve())
+            .map(|pr| curves::best_f1_threshold(&pr));
+        if let Some(best) = best {
+            threshold_scale_cloned.set_value(best.threshold);
+        }
+    });
 
-    let weights_cloned = weights.clone();
-    let bias_cloned = bias.clone();
+    let model_cloned = Rc::clone(&model);
+    let split_cell_cloned = Rc::clone(&split_cell);
+    let threshold_scale_cloned = threshold_scale.clone();
     test_button.connect_clicked(move |_| {
-        let trained_weights = weights_cloned.borrow();
-        let trained_weights = trained_weights.as_ref().unwrap();
-        let trained_bias = bias_cloned.borrow();
-        let trained_bias = trained_bias.as_ref().unwrap();
+        let trained_model = model_cloned.borrow();
+        let trained_model = trained_model.as_ref().unwrap();
 
-        let (df, (accuracy, precision, recall, f1_score)) =
-            ml::make_prediction(&test_set, &trained_weights, &trained_bias);
+        let (df, test_prediction) =
+            ml::make_prediction(&split_cell_cloned.borrow().2, trained_model.as_ref());
 
+        utils::kill_children(&diff_window);
         let tree_view = utils::create_tree_view(&df);
         tree_view.show();
 
         diff_window.add(&tree_view);
 
-        accuracy_buf.set_text(&format!("{:.3}", accuracy));
-        precision_buf.set_text(&format!("{:.3}", precision));
-        recall_buf.set_text(&format!("{:.3}", recall));
-        f1_score_buf.set_text(&format!("{:.3}", f1_score));
+        utils::kill_children(&curves_box);
+        if let Some(roc) = test_prediction.roc_curve() {
+            draw_roc_curve(&curves_box, roc, threshold_scale_cloned.clone());
+        }
+        if let Some(pr) = test_prediction.precision_recall_curve() {
+            draw_precision_recall_curve(&curves_box, pr, threshold_scale_cloned.clone());
+        }
+        if let Some(calibration) = test_prediction.calibration_curve(10) {
+            draw_calibration_curve(&curves_box, calibration);
+        }
+
+        evaluation_view.show(&test_prediction, split_options.get().seed.unwrap());
+        prediction.replace(Some(test_prediction));
+    });
+
+    // Cross Validation
+
+    let cv_box = gtk::BoxBuilder::new()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(10)
+        .build();
+    vbox.pack_start(&cv_box, false, false, 0);
+
+    cv_box.pack_start(
+        &gtk::LabelBuilder::new().label("Folds").build(),
+        false,
+        false,
+        0,
+    );
+    let folds_text = gtk::TextViewBuilder::new()
+        .buffer(&gtk::TextBufferBuilder::new().text("5").build())
+        .hexpand(true)
+        .border_width(5)
+        .build();
+    cv_box.pack_start(&folds_text, true, true, 0);
+
+    let stratified_check = gtk::CheckButtonBuilder::new()
+        .label("Stratified")
+        .sensitive(categorical)
+        .build();
+    cv_box.pack_start(&stratified_check, false, false, 0);
+
+    let cv_button = gtk::ButtonBuilder::new().label("Cross-validate").build();
+    cv_box.pack_start(&cv_button, false, false, 0);
+
+    let cv_window = gtk::ScrolledWindowBuilder::new()
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .hscrollbar_policy(gtk::PolicyType::Automatic)
+        .build();
+    vbox.pack_start(&cv_window, true, true, 0);
+
+    let cv_summary = gtk::LabelBuilder::new()
+        .halign(gtk::Align::Start)
+        .selectable(true)
+        .build();
+    vbox.pack_start(&cv_summary, false, false, 0);
+
+    cv_button.connect_clicked(move |_| {
+        let folds = utils::parse_text::<usize>(&folds_text);
+        let seed = read_seed(&seed_text);
+
+        let rows = df_cell.borrow().as_ref().unwrap().height();
+        if folds < 2 || folds > rows {
+            utils::kill_children(&cv_window);
+            cv_summary.set_text(&format!("Folds must be between 2 and {}", rows));
+            return;
+        }
+
+        if let Err(message) = create_model(seed) {
+            utils::kill_children(&cv_window);
+            cv_summary.set_text(&message);
+            return;
+        }
+
+        let results = ml::cross_validate(
+            df_cell.borrow().as_ref().unwrap(),
+            folds,
+            stratified_check.get_active(),
+            Some(seed),
+            &|| create_model(seed).unwrap(),
+        );
+
+        let mut columns = vec![Series::new(
+            "Fold",
+            (1..=results.len()).map(|x| x as f64).collect::<Vec<f64>>(),
+        )];
+        // folds don't all report the same metrics, a fold whose training set
+        // misses a class has no ROC AUC for instance
+        let mut names: Vec<&str> = Vec::new();
+        for (name, _) in results.iter().flatten() {
+            if !names.contains(name) {
+                names.push(name);
+            }
+        }
+
+        let mut summary = Vec::new();
+        for name in names {
+            let values: Vec<f64> = results
+                .iter()
+                .map(|metrics| {
+                    metrics
+                        .iter()
+                        .find(|(other, _)| *other == name)
+                        .map_or(f64::NAN, |(_, value)| *value)
+                })
+                .collect();
+            let reported: Vec<f64> = values.iter().cloned().filter(|v| !v.is_nan()).collect();
+            let (mean, std) = ml::mean_and_std(&reported);
+
+            summary.push(format!("{} {:.3} ± {:.3}", name, mean, std));
+            columns.push(Series::new(name, values));
+        }
+
+        utils::kill_children(&cv_window);
+        let tree_view = utils::create_tree_view(&DataFrame::new(columns).unwrap());
+        tree_view.show();
+        cv_window.add(&tree_view);
+
+        summary.push(format!("Seed {}", seed));
+        cv_summary.set_text(&summary.join("    "));
     });
 
     // Window
 
     window.add(&utils::wrap_in_header(
         "Model",
-        "Train and test a linear regression model on the data",
+        "Train and test a classification or regression model on the data",
         &vbox,
     ));
     window.show_all();
 }
 
-fn draw_costs_graph(container: &gtk::Box, costs: Vec<f64>, iterations: usize) {
-    utils::kill_children(container);
-
-    let low = costs
-        .iter()
-        .cloned()
+// `test_costs` pairs the cost on the test set with the number of training
+// costs recorded at that point, like `History::validation_costs`.
+fn draw_costs_graph(container: &gtk::Box, history: ml::History, test_costs: Vec<(usize, f64)>) {
+    let all_costs = || {
+        history
+            .costs
+            .iter()
+            .cloned()
+            .chain(history.validation_costs.iter().map(|(_, cost)| *cost))
+            .chain(test_costs.iter().map(|(_, cost)| *cost))
+    };
+    let low = all_costs()
         .min_by(|a, b| a.partial_cmp(b).expect("Tried to compare a NaN"))
         .unwrap()
         - 0.2;
-    let high = costs
-        .iter()
-        .cloned()
-        .min_by(|a, b| a.partial_cmp(b).expect("Tried to compare a NaN"))
+    let high = all_costs()
+        .max_by(|a, b| a.partial_cmp(b).expect("Tried to compare a NaN"))
         .unwrap()
         + 0.2;
 
@@ -175,18 +560,63 @@ fn draw_costs_graph(container: &gtk::Box, costs: Vec<f64>, iterations: usize) {
             .set_label_area_size(LabelAreaPosition::Right, 40)
             .set_label_area_size(LabelAreaPosition::Bottom, 40)
             .caption("Cost vs Iterations", ("sans-serif", 15))
-            .build_cartesian_2d(0..iterations, low..high)
+            .build_cartesian_2d(0..history.costs.len(), low..high)
             .unwrap();
 
         ctx.configure_mesh().draw().unwrap();
 
-        let graph = costs
+        let graph = history
+            .costs
             .clone()
             .into_iter()
             .enumerate()
             .map(|(idx, val)| (idx + 1, val));
 
-        ctx.draw_series(LineSeries::new(graph, &RED)).unwrap();
+        ctx.draw_series(LineSeries::new(graph, &RED))
+            .unwrap()
+            .label("Training")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+
+        if !history.validation_costs.is_empty() {
+            ctx.draw_series(LineSeries::new(history.validation_costs.clone(), &BLUE))
+                .unwrap()
+                .label("Validation")
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
+        }
+
+        if !test_costs.is_empty() {
+            ctx.draw_series(LineSeries::new(test_costs.clone(), &GREEN))
+                .unwrap()
+                .label("Test")
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &GREEN));
+        }
+
+        if !history.validation_costs.is_empty() || !test_costs.is_empty() {
+            ctx.configure_series_labels()
+                .background_style(&WHITE)
+                .border_style(&BLACK)
+                .draw()
+                .unwrap();
+        }
+
+        if let Some(best) = history.best {
+            let cost = history
+                .validation_costs
+                .iter()
+                .find(|(idx, _)| *idx == best)
+                .unwrap()
+                .1;
+            ctx.draw_series(std::iter::once(Circle::new((best, cost), 4, BLUE.filled())))
+                .unwrap();
+        }
+
+        if let Some(stopped) = history.stopped {
+            ctx.draw_series(LineSeries::new(
+                vec![(stopped, low), (stopped, high)],
+                &BLACK,
+            ))
+            .unwrap();
+        }
 
         gtk::Inhibit(false)
     });
@@ -194,6 +624,441 @@ fn draw_costs_graph(container: &gtk::Box, costs: Vec<f64>, iterations: usize) {
     container.show_all();
 }
 
+// Column and depth of a node in the drawn tree.
+type Position = (f64, usize);
+
+// Puts every leaf in its own column and centers every split above its
+// children, returns the column of `node`.
+fn layout_tree<'a>(
+    node: &'a Node,
+    depth: usize,
+    leaves: &mut usize,
+    boxes: &mut Vec<(f64, usize, &'a Node)>,
+    edges: &mut Vec<(Position, Position)>,
+) -> f64 {
+    let x = match &node.split {
+        Some(split) => {
+            let left = layout_tree(&split.left, depth + 1, leaves, boxes, edges);
+            let right = layout_tree(&split.right, depth + 1, leaves, boxes, edges);
+            let x = (left + right) / 2.;
+            edges.push(((x, depth), (left, depth + 1)));
+            edges.push(((x, depth), (right, depth + 1)));
+            x
+        }
+        None => {
+            *leaves += 1;
+            *leaves as f64 - 0.5
+        }
+    };
+    boxes.push((x, depth, node));
+    x
+}
+
+// The operating point follows the threshold scale.
+fn draw_roc_curve(container: &gtk::Box, roc: Vec<RocPoint>, threshold_scale: gtk::Scale) {
+    let auc = curves::roc_auc(&roc);
+    let points: Vec<(f64, f64)> = roc
+        .iter()
+        .map(|point| (point.false_positive_rate, point.true_positive_rate))
+        .collect();
+
+    let drawing_area = gtk::DrawingArea::new();
+    container.pack_start(&drawing_area, true, true, 0);
+
+    drawing_area.connect_draw(move |da, cr| {
+        let root_area = plotters_cairo::CairoBackend::new(
+            cr,
+            (
+                da.get_allocated_width() as u32,
+                da.get_allocated_height() as u32,
+            ),
+        )
+        .unwrap()
+        .into_drawing_area();
+
+        root_area.fill(&WHITE).unwrap();
+
+        let mut ctx = ChartBuilder::on(&root_area)
+            .margin(20)
+            .set_label_area_size(LabelAreaPosition::Left, 40)
+            .set_label_area_size(LabelAreaPosition::Bottom, 40)
+            .caption(format!("ROC Curve (AUC {:.3})", auc), ("sans-serif", 15))
+            .build_cartesian_2d(0f64..1f64, 0f64..1f64)
+            .unwrap();
+
+        ctx.configure_mesh()
+            .x_desc("False Positive Rate")
+            .y_desc("True Positive Rate")
+            .draw()
+            .unwrap();
+
+        ctx.draw_series(LineSeries::new(vec![(0., 0.), (1., 1.)], &BLACK.mix(0.3)))
+            .unwrap();
+        ctx.draw_series(LineSeries::new(points.clone(), &RED))
+            .unwrap();
+
+        // the infinite threshold of the first point is always above it
+        let threshold = threshold_scale.get_value();
+        let point = roc
+            .iter()
+            .rev()
+            .find(|point| point.threshold >= threshold)
+            .unwrap();
+        let operating_point = (point.false_positive_rate, point.true_positive_rate);
+        ctx.draw_series(std::iter::once(Circle::new(
+            operating_point,
+            5,
+            BLUE.filled(),
+        )))
+        .unwrap();
+
+        gtk::Inhibit(false)
+    });
+
+    container.show_all();
+}
+
+// The best F1 threshold is marked in green, the current one in blue.
+fn draw_precision_recall_curve(
+    container: &gtk::Box,
+    pr: Vec<PrPoint>,
+    threshold_scale: gtk::Scale,
+) {
+    let average_precision = curves::average_precision(&pr);
+    let best = curves::best_f1_threshold(&pr);
+    let points: Vec<(f64, f64)> = pr
+        .iter()
+        .map(|point| (point.recall, point.precision))
+        .collect();
+
+    let drawing_area = gtk::DrawingArea::new();
+    container.pack_start(&drawing_area, true, true, 0);
+
+    drawing_area.connect_draw(move |da, cr| {
+        let root_area = plotters_cairo::CairoBackend::new(
+            cr,
+            (
+                da.get_allocated_width() as u32,
+                da.get_allocated_height() as u32,
+            ),
+        )
+        .unwrap()
+        .into_drawing_area();
+
+        root_area.fill(&WHITE).unwrap();
+
+        let mut ctx = ChartBuilder::on(&root_area)
+            .margin(20)
+            .set_label_area_size(LabelAreaPosition::Left, 40)
+            .set_label_area_size(LabelAreaPosition::Bottom, 40)
+            .caption(
+                format!("Precision-Recall (AP {:.3})", average_precision),
+                ("sans-serif", 15),
+            )
+            .build_cartesian_2d(0f64..1f64, 0f64..1.05f64)
+            .unwrap();
+
+        ctx.configure_mesh()
+            .x_desc("Recall")
+            .y_desc("Precision")
+            .draw()
+            .unwrap();
+
+        ctx.draw_series(LineSeries::new(points.clone(), &RED))
+            .unwrap();
+
+        ctx.draw_series(std::iter::once(Circle::new(
+            (best.recall, best.precision),
+            5,
+            GREEN.filled(),
+        )))
+        .unwrap()
+        .label(format!(
+            "Best F1 {:.3} at {:.3}",
+            best.f1_score(),
+            best.threshold
+        ))
+        .legend(|(x, y)| Circle::new((x + 10, y), 5, GREEN.filled()));
+
+        // nothing is predicted positive above the highest score
+        let threshold = threshold_scale.get_value();
+        if let Some(point) = pr.iter().rev().find(|point| point.threshold >= threshold) {
+            ctx.draw_series(std::iter::once(Circle::new(
+                (point.recall, point.precision),
+                5,
+                BLUE.filled(),
+            )))
+            .unwrap();
+        }
+
+        ctx.configure_series_labels()
+            .position(SeriesLabelPosition::LowerLeft)
+            .background_style(&WHITE)
+            .border_style(&BLACK)
+            .draw()
+            .unwrap();
+
+        gtk::Inhibit(false)
+    });
+
+    container.show_all();
+}
+
+// Mean predicted probability of every bin against how often its samples are
+// actually positive, a calibrated model stays on the diagonal.
+fn draw_calibration_curve(container: &gtk::Box, calibration: Vec<CalibrationBin>) {
+    let points: Vec<(f64, f64)> = calibration
+        .iter()
+        .map(|bin| (bin.mean_probability, bin.observed_frequency))
+        .collect();
+    let largest = calibration.iter().map(|bin| bin.count).max().unwrap_or(1);
+
+    let drawing_area = gtk::DrawingArea::new();
+    container.pack_start(&drawing_area, true, true, 0);
+
+    drawing_area.connect_draw(move |da, cr| {
+        let root_area = plotters_cairo::CairoBackend::new(
+            cr,
+            (
+                da.get_allocated_width() as u32,
+                da.get_allocated_height() as u32,
+            ),
+        )
+        .unwrap()
+        .into_drawing_area();
+
+        root_area.fill(&WHITE).unwrap();
+
+        let mut ctx = ChartBuilder::on(&root_area)
+            .margin(20)
+            .set_label_area_size(LabelAreaPosition::Left, 40)
+            .set_label_area_size(LabelAreaPosition::Bottom, 40)
+            .caption("Calibration", ("sans-serif", 15))
+            .build_cartesian_2d(0f64..1f64, 0f64..1f64)
+            .unwrap();
+
+        ctx.configure_mesh()
+            .x_desc("Mean Predicted Probability")
+            .y_desc("Observed Frequency")
+            .draw()
+            .unwrap();
+
+        ctx.draw_series(LineSeries::new(vec![(0., 0.), (1., 1.)], &BLACK.mix(0.3)))
+            .unwrap();
+        ctx.draw_series(LineSeries::new(points.clone(), &RED))
+            .unwrap();
+
+        // the bigger the circle the more samples in the bin
+        ctx.draw_series(calibration.iter().map(|bin| {
+            Circle::new(
+                (bin.mean_probability, bin.observed_frequency),
+                3 + (7 * bin.count / largest) as i32,
+                RED.filled(),
+            )
+        }))
+        .unwrap();
+
+        gtk::Inhibit(false)
+    });
+
+    container.show_all();
+}
+
+fn draw_tree(
+    container: &gtk::ScrolledWindow,
+    root: Node,
+    feature_names: Vec<String>,
+    classes: Vec<f64>,
+) {
+    const COLUMN_WIDTH: i32 = 120;
+    const ROW_HEIGHT: i32 = 80;
+
+    let drawing_area = gtk::DrawingArea::new();
+    drawing_area.set_size_request(
+        root.leaves() as i32 * COLUMN_WIDTH,
+        (root.depth() as i32 + 1) * ROW_HEIGHT,
+    );
+    container.add(&drawing_area);
+
+    drawing_area.connect_draw(move |da, cr| {
+        let root_area = plotters_cairo::CairoBackend::new(
+            cr,
+            (
+                da.get_allocated_width() as u32,
+                da.get_allocated_height() as u32,
+            ),
+        )
+        .unwrap()
+        .into_drawing_area();
+
+        root_area.fill(&WHITE).unwrap();
+
+        let (mut boxes, mut edges) = (Vec::new(), Vec::new());
+        layout_tree(&root, 0, &mut 0, &mut boxes, &mut edges);
+        let to_pixels = |(x, depth): (f64, usize)| {
+            (
+                (x * COLUMN_WIDTH as f64) as i32,
+                depth as i32 * ROW_HEIGHT + ROW_HEIGHT / 2,
+            )
+        };
+
+        for (from, to) in edges {
+            root_area
+                .draw(&PathElement::new(
+                    vec![to_pixels(from), to_pixels(to)],
+                    &BLACK,
+                ))
+                .unwrap();
+        }
+
+        let style = TextStyle::from(("sans-serif", 12).into_font())
+            .pos(Pos::new(HPos::Center, VPos::Center));
+        for (x, depth, node) in boxes {
+            let (x, y) = to_pixels((x, depth));
+            let half_width = COLUMN_WIDTH / 2 - 5;
+            let corners = [(x - half_width, y - 20), (x + half_width, y + 20)];
+            root_area
+                .draw(&Rectangle::new(corners, WHITE.filled()))
+                .unwrap();
+            root_area.draw(&Rectangle::new(corners, &BLACK)).unwrap();
+
+            let title = match &node.split {
+                Some(split) => {
+                    format!("{} <= {:.3}", feature_names[split.feature], split.threshold)
+                }
+                None => {
+                    let (class, _) = node
+                        .counts
+                        .iter()
+                        .enumerate()
+                        .max_by_key(|(_, &count)| count)
+                        .unwrap();
+                    format!("Class {}", classes[class])
+                }
+            };
+            let counts: Vec<String> = node.counts.iter().map(|c| c.to_string()).collect();
+
+            root_area
+                .draw(&Text::new(title, (x, y - 8), style.clone()))
+                .unwrap();
+            root_area
+                .draw(&Text::new(
+                    format!("[{}]", counts.join(", ")),
+                    (x, y + 8),
+                    style.clone(),
+                ))
+                .unwrap();
+        }
+
+        gtk::Inhibit(false)
+    });
+
+    container.show_all();
+}
+
+fn show_metrics(metrics_box: &gtk::Grid, metrics: &[(&str, f64)], seed: u64) {
+    utils::kill_children(metrics_box);
+    for (idx, (name, value)) in metrics.iter().enumerate() {
+        add_label_and_text(metrics_box, name, idx as i32 % 2, idx as i32 / 2)
+            .set_text(&format!("{:.3}", value));
+    }
+    let y = (metrics.len() as i32 + 1) / 2;
+    add_label_and_text(metrics_box, "Seed", 0, y).set_text(&seed.to_string());
+    metrics_box.show_all();
+}
+
+// The widgets showing how a prediction scores, refreshed whenever the
+// threshold or the averaging changes without touching the model.
+#[derive(Clone)]
+struct EvaluationView {
+    metrics_box: gtk::Grid,
+    confusion_grid: gtk::Grid,
+    threshold_scale: gtk::Scale,
+    best_f1_button: gtk::Button,
+    average_combo: gtk::ComboBoxText,
+    test_button: gtk::Button,
+}
+
+impl EvaluationView {
+    // Empties the metrics and disables the controls until the next test, and
+    // testing until the next model is trained.
+    fn clear(&self) {
+        utils::kill_children(&self.metrics_box);
+        utils::kill_children(&self.confusion_grid);
+        self.threshold_scale.set_sensitive(false);
+        self.best_f1_button.set_sensitive(false);
+        self.average_combo.set_sensitive(false);
+        self.test_button.set_sensitive(false);
+    }
+
+    fn show(&self, prediction: &ml::Prediction, seed: u64) {
+        self.threshold_scale.set_sensitive(prediction.is_binary());
+        self.best_f1_button.set_sensitive(prediction.is_binary());
+        self.average_combo
+            .set_sensitive(prediction.probabilities.is_some() && prediction.classes.len() > 2);
+
+        let threshold = if prediction.is_binary() {
+            Some(self.threshold_scale.get_value())
+        } else {
+            None
+        };
+        let average = self.average_combo.get_active_text().unwrap();
+
+        show_metrics(
+            &self.metrics_box,
+            &prediction.metrics(threshold, &average),
+            seed,
+        );
+
+        utils::kill_children(&self.confusion_grid);
+        if prediction.probabilities.is_some() {
+            show_confusion_matrix(
+                &self.confusion_grid,
+                &prediction.confusion_matrix(threshold),
+            );
+        }
+    }
+}
+
+// Actual classes down the rows, predicted ones across the columns, with the
+// correct predictions in bold.
+fn show_confusion_matrix(grid: &gtk::Grid, matrix: &ConfusionMatrix) {
+    let label = |text: &str| {
+        gtk::LabelBuilder::new()
+            .label(text)
+            .use_markup(true)
+            .build()
+    };
+
+    grid.attach(&label("Actual \\ Predicted"), 0, 0, 1, 1);
+    for (idx, class) in matrix.classes.iter().enumerate() {
+        grid.attach(
+            &label(&format!("<b>{}</b>", class)),
+            idx as i32 + 1,
+            0,
+            1,
+            1,
+        );
+        grid.attach(
+            &label(&format!("<b>{}</b>", class)),
+            0,
+            idx as i32 + 1,
+            1,
+            1,
+        );
+    }
+    for ((row, col), count) in matrix.counts.indexed_iter() {
+        let text = if row == col {
+            format!("<b>{}</b>", count)
+        } else {
+            count.to_string()
+        };
+        grid.attach(&label(&text), col as i32 + 1, row as i32 + 1, 1, 1);
+    }
+
+    grid.show_all();
+}
+
 fn add_label_and_text(grid: &gtk::Grid, label: &str, x: i32, y: i32) -> gtk::TextBuffer {
     grid.attach(
         &gtk::LabelBuilder::new()
@@ -215,3 +1080,80 @@ fn add_label_and_text(grid: &gtk::Grid, label: &str, x: i32, y: i32) -> gtk::Tex
 
     text.get_buffer().unwrap()
 }
+
+struct SplitForm {
+    seed_text: gtk::TextView,
+    train_ratio_text: gtk::TextView,
+    validation_ratio_text: gtk::TextView,
+    shuffle_check: gtk::CheckButton,
+    stratified_check: gtk::CheckButton,
+}
+
+impl SplitForm {
+    fn read(&self) -> ml::SplitOptions {
+        ml::SplitOptions {
+            train_ratio: utils::parse_text(&self.train_ratio_text),
+            validation_ratio: utils::parse_text(&self.validation_ratio_text),
+            shuffle: self.shuffle_check.get_active(),
+            stratified: self.stratified_check.get_active(),
+            seed: Some(read_seed(&self.seed_text)),
+        }
+    }
+}
+
+fn show_split_sizes(
+    label: &gtk::Label,
+    (train_set, validation_set, test_set): &(Array2<f64>, Option<Array2<f64>>, Array2<f64>),
+) {
+    // a continuous target has about as many values as rows, it only gets the
+    // number of distinct values
+    let describe = |name: &str, set: &Array2<f64>| {
+        let class_counts = ml::class_counts(set);
+        if class_counts.len() > ml::MAX_CLASSES {
+            return format!("{} {} ({} values)", name, set.nrows(), class_counts.len());
+        }
+
+        let counts: Vec<String> = class_counts
+            .into_iter()
+            .map(|(class, count)| format!("{}: {}", class, count))
+            .collect();
+        format!("{} {} ({})", name, set.nrows(), counts.join(", "))
+    };
+
+    let mut sizes = vec![describe("Train", train_set)];
+    if let Some(validation_set) = validation_set {
+        sizes.push(describe("Validation", validation_set));
+    }
+    sizes.push(describe("Test", test_set));
+
+    label.set_text(&sizes.join("    "));
+}
+
+// An empty seed gets replaced by a random one so the seed that was actually
+// used is always on screen.
+fn read_seed(text_view: &gtk::TextView) -> u64 {
+    let buffer = text_view.get_buffer().unwrap();
+    if utils::get_text(buffer.clone()).trim().is_empty() {
+        buffer.set_text(&rand::random::<u32>().to_string());
+    }
+
+    utils::parse_text(text_view)
+}
+
+fn add_param(grid: &gtk::Grid, label: &str, default: &str, x: i32, y: i32) -> gtk::TextView {
+    grid.attach(
+        &gtk::LabelBuilder::new().label(label).build(),
+        x * 2,
+        y,
+        1,
+        1,
+    );
+    let text = gtk::TextViewBuilder::new()
+        .buffer(&gtk::TextBufferBuilder::new().text(default).build())
+        .hexpand(true)
+        .border_width(5)
+        .build();
+    grid.attach(&text, x * 2 + 1, y, 1, 1);
+
+    text
+}
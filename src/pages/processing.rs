@@ -1,6 +1,9 @@
 use super::{paint, Pages};
+use crate::ml::pca::Pca;
 use crate::utils;
 use gtk::prelude::*;
+use ndarray::prelude::*;
+use plotters::prelude::*;
 use polars::prelude::*;
 
 use std::cell::RefCell;
@@ -20,6 +23,34 @@ pub fn render_page(
     let normalize_button = gtk::ButtonBuilder::new().label("Normalize").build();
     vbox.pack_start(&normalize_button, false, false, 0);
 
+    let pca_box = gtk::BoxBuilder::new()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(10)
+        .build();
+    vbox.pack_start(&pca_box, false, false, 0);
+
+    pca_box.pack_start(
+        &gtk::LabelBuilder::new().label("Components").build(),
+        false,
+        false,
+        0,
+    );
+    let components_text = gtk::TextViewBuilder::new()
+        .buffer(&gtk::TextBufferBuilder::new().text("2").build())
+        .hexpand(true)
+        .border_width(5)
+        .build();
+    pca_box.pack_start(&components_text, true, true, 0);
+
+    let pca_button = gtk::ButtonBuilder::new().label("PCA").build();
+    pca_box.pack_start(&pca_button, false, false, 0);
+
+    let graphs_box = gtk::BoxBuilder::new()
+        .orientation(gtk::Orientation::Horizontal)
+        .homogeneous(true)
+        .build();
+    vbox.pack_start(&graphs_box, true, true, 0);
+
     let scroll_window = gtk::ScrolledWindowBuilder::new()
         .vscrollbar_policy(gtk::PolicyType::Automatic)
         .hscrollbar_policy(gtk::PolicyType::Automatic)
@@ -31,6 +62,24 @@ pub fn render_page(
     tree_view.show();
     scroll_window.add(&tree_view);
 
+    let scroll_window_clone = scroll_window.clone();
+    let df_cell_cloned = Rc::clone(&df_cell);
+    pca_button.connect_clicked(move |_| {
+        let n = utils::parse_text::<usize>(&components_text);
+        let (projected_df, pca, projection, targets) =
+            project_dataframe(df_cell_cloned.borrow().as_ref().unwrap(), n);
+        df_cell_cloned.replace(Some(projected_df.clone()));
+
+        utils::kill_children(&scroll_window_clone);
+        let tree_view = utils::create_tree_view(&projected_df);
+        tree_view.show();
+        scroll_window_clone.add(&tree_view);
+
+        utils::kill_children(&graphs_box);
+        draw_scree_plot(&graphs_box, pca.explained_variance_ratio.to_vec());
+        draw_projection(&graphs_box, projection, targets);
+    });
+
     let scroll_window_clone = scroll_window.clone();
     let df_cell_cloned = Rc::clone(&df_cell);
     normalize_button.connect_clicked(move |_| {
@@ -46,12 +95,21 @@ pub fn render_page(
 
     let next_page_button = gtk::ButtonBuilder::new().label("Model").build();
     let window_clone = window.clone();
+    let page_cell_cloned = Rc::clone(&page_cell);
     next_page_button.connect_clicked(move |_| {
-        *page_cell.borrow_mut() = Pages::Model;
+        *page_cell_cloned.borrow_mut() = Pages::Model;
         paint(&window_clone);
     });
     vbox.pack_start(&next_page_button, false, false, 0);
 
+    let cluster_page_button = gtk::ButtonBuilder::new().label("Cluster").build();
+    let window_clone = window.clone();
+    cluster_page_button.connect_clicked(move |_| {
+        *page_cell.borrow_mut() = Pages::Cluster;
+        paint(&window_clone);
+    });
+    vbox.pack_start(&cluster_page_button, false, false, 0);
+
     window.add(&utils::wrap_in_header(
         "Processing",
         "Apply transformations on the data",
@@ -83,3 +141,167 @@ fn normalize_dataframe(df: &DataFrame) -> DataFrame {
     )
     .unwrap()
 }
+
+// Replaces the feature columns with their first `n` principal components, the
+// target stays in the last column. Also returns the projection onto the first
+// two components and the targets for plotting.
+fn project_dataframe(df: &DataFrame, n: usize) -> (DataFrame, Pca, Array2<f64>, Vec<f64>) {
+    let matrix = df.to_ndarray::<Float64Type>().unwrap();
+    let features = matrix.slice(s![.., ..-1]).to_owned();
+    let targets = matrix.column(matrix.ncols() - 1).to_vec();
+
+    let pca = Pca::fit(&features);
+    let components = pca.transform(&features, n);
+
+    let mut columns: Vec<Series> = components
+        .gencolumns()
+        .into_iter()
+        .enumerate()
+        .map(|(idx, column)| Series::new(&format!("PC{}", idx + 1), column.to_vec()))
+        .collect();
+    columns.push(Series::new(
+        df.get_column_names().last().unwrap(),
+        targets.clone(),
+    ));
+
+    let projection = pca.transform(&features, 2);
+    (DataFrame::new(columns).unwrap(), pca, projection, targets)
+}
+
+fn draw_scree_plot(container: &gtk::Box, ratios: Vec<f64>) {
+    let cumulative: Vec<(usize, f64)> = ratios
+        .iter()
+        .scan(0., |total, ratio| {
+            *total += ratio;
+            Some(*total)
+        })
+        .enumerate()
+        .map(|(idx, total)| (idx + 1, total))
+        .collect();
+    let ratios: Vec<(usize, f64)> = ratios
+        .into_iter()
+        .enumerate()
+        .map(|(idx, ratio)| (idx + 1, ratio))
+        .collect();
+
+    let drawing_area = gtk::DrawingArea::new();
+    container.pack_start(&drawing_area, true, true, 0);
+
+    drawing_area.connect_draw(move |da, cr| {
+        let root_area = plotters_cairo::CairoBackend::new(
+            cr,
+            (
+                da.get_allocated_width() as u32,
+                da.get_allocated_height() as u32,
+            ),
+        )
+        .unwrap()
+        .into_drawing_area();
+
+        root_area.fill(&WHITE).unwrap();
+
+        let mut ctx = ChartBuilder::on(&root_area)
+            .margin(20)
+            .set_label_area_size(LabelAreaPosition::Left, 40)
+            .set_label_area_size(LabelAreaPosition::Bottom, 40)
+            .caption("Explained Variance Ratio", ("sans-serif", 15))
+            .build_cartesian_2d(0..ratios.len() + 1, 0f64..1.05)
+            .unwrap();
+
+        ctx.configure_mesh().draw().unwrap();
+
+        ctx.draw_series(LineSeries::new(ratios.clone(), &RED))
+            .unwrap()
+            .label("Component")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+        ctx.draw_series(
+            ratios
+                .iter()
+                .map(|&point| Circle::new(point, 3, RED.filled())),
+        )
+        .unwrap();
+
+        ctx.draw_series(LineSeries::new(cumulative.clone(), &BLUE))
+            .unwrap()
+            .label("Cumulative")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
+
+        ctx.configure_series_labels()
+            .position(SeriesLabelPosition::MiddleRight)
+            .background_style(&WHITE)
+            .border_style(&BLACK)
+            .draw()
+            .unwrap();
+
+        gtk::Inhibit(false)
+    });
+
+    container.show_all();
+}
+
+// Scatter of the first two components with a colour per target value.
+fn draw_projection(container: &gtk::Box, projection: Array2<f64>, targets: Vec<f64>) {
+    let mut classes = targets.clone();
+    classes.sort_by(|a, b| a.partial_cmp(b).expect("Tried to compare a NaN"));
+    classes.dedup();
+
+    let range = |column: usize| {
+        let values = projection.column(column.min(projection.ncols() - 1));
+        let low = values.fold(f64::INFINITY, |acc, &x| acc.min(x));
+        let high = values.fold(f64::NEG_INFINITY, |acc, &x| acc.max(x));
+        let pad = ((high - low) * 0.05).max(1e-3);
+        (low - pad)..(high + pad)
+    };
+    let (x_range, y_range) = (range(0), range(1));
+
+    let drawing_area = gtk::DrawingArea::new();
+    container.pack_start(&drawing_area, true, true, 0);
+
+    drawing_area.connect_draw(move |da, cr| {
+        let root_area = plotters_cairo::CairoBackend::new(
+            cr,
+            (
+                da.get_allocated_width() as u32,
+                da.get_allocated_height() as u32,
+            ),
+        )
+        .unwrap()
+        .into_drawing_area();
+
+        root_area.fill(&WHITE).unwrap();
+
+        let mut ctx = ChartBuilder::on(&root_area)
+            .margin(20)
+            .set_label_area_size(LabelAreaPosition::Left, 40)
+            .set_label_area_size(LabelAreaPosition::Bottom, 40)
+            .caption("PC1 vs PC2", ("sans-serif", 15))
+            .build_cartesian_2d(x_range.clone(), y_range.clone())
+            .unwrap();
+
+        ctx.configure_mesh().draw().unwrap();
+
+        for (idx, class) in classes.iter().enumerate() {
+            let color = Palette99::pick(idx);
+            let points = projection
+                .outer_iter()
+                .zip(targets.iter())
+                .filter(|(_, target)| *target == class)
+                .map(|(row, _)| (row[0], if row.len() > 1 { row[1] } else { 0. }));
+
+            ctx.draw_series(points.map(|point| Circle::new(point, 3, color.filled())))
+                .unwrap()
+                .label(class.to_string())
+                .legend(move |(x, y)| Circle::new((x + 10, y), 3, color.filled()));
+        }
+
+        ctx.configure_series_labels()
+            .background_style(&WHITE)
+            .border_style(&BLACK)
+            .draw()
+            .unwrap();
+
+        gtk::Inhibit(false)
+    });
+
+    container.show_all();
+}
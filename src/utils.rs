@@ -78,3 +78,14 @@ pub fn get_text(buffer: gtk::TextBuffer) -> String {
         .unwrap()
         .to_string()
 }
+
+pub fn parse_text<T>(text_view: &gtk::TextView) -> T
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Debug,
+{
+    get_text(text_view.get_buffer().unwrap())
+        .trim()
+        .parse::<T>()
+        .unwrap()
+}